@@ -0,0 +1,269 @@
+//! Renders markdown changelog sections from conventional commits.
+
+use crate::{ChangelogConfig, Conventional, LogItem};
+use std::fmt::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{fs, io};
+
+const OTHER_TITLE: &str = "Other Changes";
+
+struct Entry<'a> {
+    commit: &'a str,
+    ty: &'a str,
+    scope: Option<&'a str>,
+    subject: &'a str,
+    breaking: Option<&'a str>,
+}
+
+impl<'a> Entry<'a> {
+    fn from_item(item: &'a LogItem) -> Option<Self> {
+        let conv = Conventional::parse(&item.message)?;
+
+        Some(Entry {
+            commit: &item.commit,
+            ty: conv.ty,
+            scope: conv.scope,
+            subject: conv.subject,
+            breaking: Some(conv.breaking_change.unwrap_or("")).filter(|_| conv.breaking),
+        })
+    }
+}
+
+fn write_commit_link(out: &mut String, commit: &str, config: &ChangelogConfig) {
+    let short = &commit[..std::cmp::min(7, commit.len())];
+
+    match config.commit_url {
+        Some(ref url) => write!(out, " ([{}]({}))", short, url.replace("{hash}", commit)),
+        None => write!(out, " ({})", short),
+    }
+    .unwrap();
+}
+
+fn write_section(out: &mut String, title: &str, entries: &[&Entry], config: &ChangelogConfig) {
+    if entries.is_empty() {
+        return;
+    }
+
+    write!(out, "### {}\n\n", title).unwrap();
+
+    for entry in entries.iter().filter(|entry| entry.scope.is_none()) {
+        out.push_str("* ");
+        out.push_str(entry.subject);
+        write_commit_link(out, entry.commit, config);
+        out.push('\n');
+    }
+
+    let mut scopes: Vec<&str> = entries.iter().filter_map(|entry| entry.scope).collect();
+    scopes.sort_unstable();
+    scopes.dedup();
+
+    for scope in scopes {
+        let scoped: Vec<_> = entries
+            .iter()
+            .filter(|entry| entry.scope == Some(scope))
+            .collect();
+
+        if let [entry] = scoped.as_slice() {
+            write!(out, "* **{}:** {}", scope, entry.subject).unwrap();
+            write_commit_link(out, entry.commit, config);
+            out.push('\n');
+        } else {
+            writeln!(out, "* **{}:**", scope).unwrap();
+            for entry in scoped {
+                write!(out, "  * {}", entry.subject).unwrap();
+                write_commit_link(out, entry.commit, config);
+                out.push('\n');
+            }
+        }
+    }
+
+    out.push('\n');
+}
+
+/// Renders a release section titled `title` for `items`. Commits that aren't conventional are
+/// left out, as are hidden types, except for breaking changes.
+pub fn render(title: &str, date: &str, items: &[LogItem], config: &ChangelogConfig) -> String {
+    let entries: Vec<Entry> = items.iter().filter_map(Entry::from_item).collect();
+
+    let mut out = String::new();
+    write!(out, "## {} ({})\n\n", title, date).unwrap();
+
+    let breaking: Vec<&Entry> = entries
+        .iter()
+        .filter(|entry| entry.breaking.is_some())
+        .collect();
+    if !breaking.is_empty() {
+        out.push_str("### ⚠ BREAKING CHANGES\n\n");
+        for entry in breaking {
+            out.push_str("* ");
+            if let Some(scope) = entry.scope {
+                write!(out, "**{}:** ", scope).unwrap();
+            }
+            out.push_str(
+                entry
+                    .breaking
+                    .filter(|note| !note.is_empty())
+                    .unwrap_or(entry.subject),
+            );
+            write_commit_link(&mut out, entry.commit, config);
+            out.push('\n');
+        }
+        out.push('\n');
+    }
+
+    let is_visible = |ty: &str| !config.hidden.iter().any(|hidden| hidden == ty);
+
+    for section in config.sections.iter().filter(|s| is_visible(&s.ty)) {
+        let matching: Vec<&Entry> = entries.iter().filter(|e| e.ty == section.ty).collect();
+        write_section(&mut out, &section.title, &matching, config);
+    }
+
+    let other: Vec<&Entry> = entries
+        .iter()
+        .filter(|entry| is_visible(entry.ty))
+        .filter(|entry| !config.sections.iter().any(|s| s.ty == entry.ty))
+        .collect();
+    write_section(&mut out, OTHER_TITLE, &other, config);
+
+    out
+}
+
+/// Inserts `section` above the first release (`## ` heading) in `existing`, keeping any title or
+/// introduction at the top of the file.
+pub fn prepend(existing: &str, section: &str) -> String {
+    let offset = if existing.starts_with("## ") {
+        Some(0)
+    } else {
+        existing.find("\n## ").map(|i| i + 1)
+    };
+
+    match offset {
+        Some(offset) => format!("{}{}{}", &existing[..offset], section, &existing[offset..]),
+        None if existing.trim().is_empty() => format!("# Changelog\n\n{}", section),
+        None => format!("{}\n\n{}", existing.trim_end(), section),
+    }
+}
+
+/// Reads the changelog at `path` to prepend to. A missing file reads as empty, but any other
+/// error is returned, so an unreadable changelog is never overwritten.
+pub fn read_existing(path: &Path) -> io::Result<String> {
+    match fs::read_to_string(path) {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+        result => result,
+    }
+}
+
+/// Formats a unix timestamp as a UTC `YYYY-MM-DD` date.
+pub fn format_date(epoch_secs: i64) -> String {
+    // Howard Hinnant's days-to-civil algorithm
+    let days = epoch_secs.div_euclid(86_400) + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+pub fn today() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+
+    format_date(now)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{format_date, prepend, read_existing, render};
+    use crate::{ChangelogConfig, LogItem};
+    use std::{env, fs, io, process};
+
+    #[test]
+    fn renders_sections() {
+        let items = vec![
+            LogItem::test("feat(api): add users endpoint").commit("1111111aaa"),
+            LogItem::test("fix: crash on start").commit("2222222bbb"),
+            LogItem::test("feat(api): add teams endpoint").commit("3333333ccc"),
+            LogItem::test("chore: bump deps").commit("4444444ddd"),
+            LogItem::test("feat(client): drop IE\n\nBREAKING CHANGE: IE11 is no longer supported")
+                .commit("5555555eee"),
+            LogItem::test("not conventional").commit("6666666fff"),
+            LogItem::test("ci!: require node 14").commit("7777777aaa"),
+        ];
+        let config = ChangelogConfig {
+            commit_url: Some("https://example.com/{hash}".into()),
+            ..Default::default()
+        };
+
+        let out = render("1.0.0", "2020-01-01", &items, &config);
+        assert_eq!(
+            out,
+            "## 1.0.0 (2020-01-01)
+
+### ⚠ BREAKING CHANGES
+
+* **client:** IE11 is no longer supported ([5555555](https://example.com/5555555eee))
+* require node 14 ([7777777](https://example.com/7777777aaa))
+
+### Features
+
+* **api:**
+  * add users endpoint ([1111111](https://example.com/1111111aaa))
+  * add teams endpoint ([3333333](https://example.com/3333333ccc))
+* **client:** drop IE ([5555555](https://example.com/5555555eee))
+
+### Bug Fixes
+
+* crash on start ([2222222](https://example.com/2222222bbb))
+
+"
+        );
+    }
+
+    #[test]
+    fn prepends_below_title() {
+        let existing = "# Changelog\n\nIntro.\n\n## 0.1.0 (2019-01-01)\n\n* first\n";
+        assert_eq!(
+            prepend(existing, "## 0.2.0 (2020-01-01)\n\n"),
+            "# Changelog\n\nIntro.\n\n## 0.2.0 (2020-01-01)\n\n## 0.1.0 (2019-01-01)\n\n* first\n"
+        );
+        assert_eq!(
+            prepend("", "## 0.1.0 (2019-01-01)\n\n"),
+            "# Changelog\n\n## 0.1.0 (2019-01-01)\n\n"
+        );
+    }
+
+    #[test]
+    fn reads_existing() {
+        let dir = env::temp_dir().join(format!("glint-changelog-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let missing = dir.join("MISSING.md");
+        assert_eq!(read_existing(&missing).unwrap(), "");
+
+        let invalid = dir.join("INVALID.md");
+        fs::write(&invalid, b"## 0.1.0\n\xff\n").unwrap();
+        let err = read_existing(&invalid).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // A directory can't be read as a file
+        assert!(read_existing(&dir).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn formats_dates() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(1568585467), "2019-09-15");
+        assert_eq!(format_date(951_782_400), "2000-02-29");
+    }
+}
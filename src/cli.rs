@@ -1,7 +1,8 @@
+use std::path::PathBuf;
 use structopt::clap::AppSettings;
 use structopt::StructOpt;
 
-#[derive(StructOpt)]
+#[derive(StructOpt, Default)]
 pub struct Commit {
     /// Sets the 'type' component of the commit (optional; otherwise interactive prompt)
     #[structopt(short, long)]
@@ -18,6 +19,19 @@ pub struct Commit {
     #[structopt(short, long)]
     pub all: bool,
 
+    /// Writes the body in the editor git uses for commit messages, before reviewing it in the
+    /// body prompt
+    #[structopt(short, long, conflicts_with = "message")]
+    pub editor: bool,
+
+    /// Marks the commit as a breaking change by adding '!' after the type/scope
+    #[structopt(short, long)]
+    pub breaking: bool,
+
+    /// Describes the breaking change in a 'BREAKING CHANGE' footer (implies --breaking)
+    #[structopt(long)]
+    pub breaking_change: Option<String>,
+
     /// Arguments which will be passed to 'git commit'.
     /// Pass a '--' argument before the git args to disable special parsing.
     #[structopt(short, long)]
@@ -26,22 +40,29 @@ pub struct Commit {
 
 #[derive(StructOpt)]
 pub struct Log {
-    /// Filter by 'type' e.g. 'feat'
-    #[structopt(short, long)]
-    pub ty: Option<String>,
+    /// Filter by 'type' e.g. 'feat'. May be repeated or comma-separated, and a leading '!'
+    /// excludes a type, e.g. '!chore'
+    #[structopt(short, long, number_of_values = 1)]
+    pub ty: Vec<String>,
 
-    /// Filter by 'scope' e.g. 'client'
-    #[structopt(short, long)]
-    pub scope: Option<String>,
+    /// Filter by 'scope' e.g. 'client'. Supports the same syntax as '--ty', plus globs like
+    /// 'api/*'. Commits with several scopes match if any of them does
+    #[structopt(short, long, number_of_values = 1)]
+    pub scope: Vec<String>,
 
-    /// Number of commits to display.
+    /// Number of commits to display, after filtering. Defaults to the terminal height, or
+    /// every commit with '--json' and '--ndjson'.
     #[structopt(short, long)]
     pub num: Option<usize>,
 
     /// Output log info as a JSON array of objects
-    #[structopt(short, long)]
+    #[structopt(short, long, conflicts_with = "ndjson")]
     pub json: bool,
 
+    /// Output log info as one JSON object per line, written as each commit is read
+    #[structopt(long)]
+    pub ndjson: bool,
+
     /// Only useful when filing bug reports for glint.
     #[structopt(short, long)]
     pub debug: bool,
@@ -52,6 +73,96 @@ pub struct Log {
     pub git_args: Vec<String>,
 }
 
+#[derive(StructOpt)]
+pub struct Lint {
+    /// Read the message from a file, such as '.git/COMMIT_EDITMSG'. Lines starting
+    /// with '#' are ignored, as git would.
+    #[structopt(short, long, parse(from_os_str), conflicts_with = "range")]
+    pub file: Option<PathBuf>,
+
+    /// A revision range to check, e.g. 'origin/main..HEAD'. If neither this nor
+    /// '--file' is given, the message is read from stdin.
+    pub range: Option<String>,
+}
+
+#[derive(StructOpt)]
+pub struct Changelog {
+    /// Title of the new section, usually the version being released
+    #[structopt(short, long, default_value = "Unreleased")]
+    pub release: String,
+
+    /// Insert the section into this file (e.g. CHANGELOG.md) above the previous
+    /// release, instead of printing it
+    #[structopt(short, long, parse(from_os_str))]
+    pub prepend: Option<PathBuf>,
+
+    /// The commits to include, e.g. 'v1.2.0..HEAD'. Defaults to the commits since
+    /// the latest semver tag.
+    pub range: Option<String>,
+}
+
+#[derive(StructOpt)]
+pub struct Bump {
+    /// Below 1.0.0, bump the minor version for breaking changes and the patch
+    /// version for features
+    #[structopt(long)]
+    pub pre_major: bool,
+
+    /// Make a pre-release with this identifier, e.g. 'rc' for '1.3.0-rc.0'
+    #[structopt(long)]
+    pub prerelease: Option<String>,
+
+    /// Create an annotated tag for the new version
+    #[structopt(long)]
+    pub tag: bool,
+}
+
+#[derive(StructOpt)]
+pub struct Stats {
+    /// Only count commits more recent than this, e.g. '2 weeks ago' or '2020-01-31'
+    #[structopt(long)]
+    pub since: Option<String>,
+
+    /// Only count commits older than this
+    #[structopt(long)]
+    pub until: Option<String>,
+
+    /// Number of files to show for each scope
+    #[structopt(long, default_value = "3")]
+    pub top_files: usize,
+
+    /// Output the report as JSON
+    #[structopt(short, long)]
+    pub json: bool,
+
+    /// A revision range, e.g. 'v1.2.0..HEAD'. Defaults to all of HEAD's history.
+    pub range: Option<String>,
+}
+
+#[derive(StructOpt)]
+pub enum Hook {
+    /// Install git hooks that run glint. Existing hooks are kept and run first.
+    Install {
+        /// Hooks to install: 'commit-msg' and/or 'prepare-commit-msg' (default: both)
+        hooks: Vec<String>,
+    },
+
+    /// Remove glint's git hooks and restore any hooks they replaced
+    Uninstall {
+        /// Hooks to remove (default: both)
+        hooks: Vec<String>,
+    },
+
+    /// Run a hook. This is what the installed hook scripts call.
+    Run {
+        /// The name of the hook, e.g. 'commit-msg'
+        hook: String,
+
+        /// The arguments git passed to the hook
+        args: Vec<String>,
+    },
+}
+
 /// A friendly conventional commit tool. You probably want the 'commit' subcommand, or 'c' for short.
 #[derive(StructOpt)]
 pub enum Cli {
@@ -60,6 +171,22 @@ pub enum Cli {
 
     /// View recent commits
     Log(Log),
+
+    /// Check commit messages against the configured rules
+    Lint(Lint),
+
+    /// Manage git hooks for validating and writing commit messages
+    Hook(Hook),
+
+    /// Generate a changelog section from conventional commits
+    Changelog(Changelog),
+
+    /// Print the next semantic version based on the commits since the latest tag
+    #[structopt(alias = "next-version")]
+    Bump(Bump),
+
+    /// Summarize commits by type, scope, author, and week
+    Stats(Stats),
 }
 
 pub fn parse() -> Cli {
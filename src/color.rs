@@ -16,6 +16,31 @@ pub fn theme_user_input() -> Color {
     Color::Blue
 }
 
+/// Parses a colour name from the config, like "red" or "dark_blue".
+pub fn from_name(name: &str) -> Option<Color> {
+    let color = match name.to_ascii_lowercase().replace('-', "_").as_str() {
+        "black" => Color::Black,
+        "dark_grey" | "dark_gray" => Color::DarkGrey,
+        "red" => Color::Red,
+        "dark_red" => Color::DarkRed,
+        "green" => Color::Green,
+        "dark_green" => Color::DarkGreen,
+        "yellow" => Color::Yellow,
+        "dark_yellow" => Color::DarkYellow,
+        "blue" => Color::Blue,
+        "dark_blue" => Color::DarkBlue,
+        "magenta" => Color::Magenta,
+        "dark_magenta" => Color::DarkMagenta,
+        "cyan" => Color::Cyan,
+        "dark_cyan" => Color::DarkCyan,
+        "white" => Color::White,
+        "grey" | "gray" => Color::Grey,
+        _ => return None,
+    };
+
+    Some(color)
+}
+
 // pub fn glint_type_to_color(ty: &str) -> Color {
 //     match ty {
 //         "build" => Color::White,
@@ -1,5 +1,15 @@
+mod bump;
+mod changelog;
 mod commit;
+mod hook;
+mod lint;
 mod log;
+mod stats;
 
+pub use bump::bump;
+pub use changelog::changelog;
 pub use commit::commit;
+pub use hook::hook;
+pub use lint::lint;
 pub use log::log;
+pub use stats::stats;
@@ -0,0 +1,75 @@
+use crate::cli;
+use glint::version::{self, Version};
+use glint::{Config, Git};
+use std::io;
+
+pub fn bump(params: cli::Bump, _config: Config) {
+    let git = match Git::from_cwd() {
+        Ok(git) => git,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let latest = match git.latest_tag() {
+        Ok(latest) => latest,
+        Err(err) => {
+            eprintln!("Failed to find the latest tag: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let (range, previous, prefix) = match latest {
+        Some((tag, version)) => {
+            let prefix = if tag.starts_with('v') { "v" } else { "" };
+            (format!("{}..HEAD", tag), version, prefix)
+        }
+        None => ("HEAD".to_string(), Version::new(0, 0, 0), "v"),
+    };
+
+    let items = match git
+        .log_parsed(&[range])
+        .and_then(|logs| logs.collect::<io::Result<Vec<_>>>())
+    {
+        Ok(items) => items,
+        Err(err) => {
+            eprintln!("Failed to read the git log: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let bump = match version::next_bump(&items) {
+        Some(bump) => bump,
+        None => {
+            eprintln!("No feat, fix, or breaking commits since {}.", previous);
+            println!("{}", previous);
+            return;
+        }
+    };
+
+    let mut next = previous.bump(bump, params.pre_major);
+    if let Some(ref identifier) = params.prerelease {
+        next = next.with_prerelease(identifier, &previous);
+    }
+
+    eprintln!("{} -> {} ({})", previous, next, bump);
+    println!("{}", next);
+
+    if params.tag {
+        let name = format!("{}{}", prefix, next);
+        let message = format!("chore(release): {}", next);
+
+        match git.tag(&name, &message).status() {
+            Ok(status) if status.success() => eprintln!("Created tag {}", name),
+            Ok(_) => {
+                eprintln!("Failed to create tag {}", name);
+                std::process::exit(1);
+            }
+            Err(err) => {
+                eprintln!("Failed to run git: {}", err);
+                std::process::exit(1);
+            }
+        }
+    }
+}
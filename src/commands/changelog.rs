@@ -0,0 +1,59 @@
+use crate::cli;
+use glint::{changelog, Config, Git};
+use std::{fs, io};
+
+pub fn changelog(params: cli::Changelog, config: Config) {
+    let git = match Git::from_cwd() {
+        Ok(git) => git,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let range = match params.range {
+        Some(range) => range,
+        None => match git.latest_tag() {
+            Ok(Some((tag, _))) => format!("{}..HEAD", tag),
+            Ok(None) => "HEAD".to_string(),
+            Err(err) => {
+                eprintln!("Failed to find the latest tag: {}", err);
+                std::process::exit(1);
+            }
+        },
+    };
+
+    let items = match git
+        .log_parsed(&[range])
+        .and_then(|logs| logs.collect::<io::Result<Vec<_>>>())
+    {
+        Ok(items) => items,
+        Err(err) => {
+            eprintln!("Failed to read the git log: {}", err);
+            std::process::exit(1);
+        }
+    };
+    let section = changelog::render(
+        &params.release,
+        &changelog::today(),
+        &items,
+        &config.changelog,
+    );
+
+    match params.prepend {
+        Some(path) => {
+            let existing = match changelog::read_existing(&path) {
+                Ok(existing) => existing,
+                Err(err) => {
+                    eprintln!("Failed to read {}: {}", path.display(), err);
+                    std::process::exit(1);
+                }
+            };
+            if let Err(err) = fs::write(&path, changelog::prepend(&existing, &section)) {
+                eprintln!("Failed to write {}: {}", path.display(), err);
+                std::process::exit(1);
+            }
+        }
+        None => print!("{}", section),
+    }
+}
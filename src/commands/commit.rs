@@ -1,6 +1,6 @@
 use crate::cli;
 use crossterm::{self as ct, cursor, terminal};
-use glint::{prompt, Commit, Config, Git};
+use glint::{editor, prompt, scopes, Commit, Config, Footer, Git};
 use std::io::Write as _Write;
 
 fn with_raw<R>(f: impl FnOnce() -> R) -> R {
@@ -22,6 +22,18 @@ fn exit<R>(code: i32) -> Option<R> {
     std::process::exit(code)
 }
 
+/// The interactive steps of building a commit, in order.
+enum Stage {
+    Files,
+    Type,
+    Scope(String),
+    Subject(Commit),
+    Body(Commit),
+    Footers(Commit),
+    Review(Commit),
+    Complete(Commit),
+}
+
 pub fn commit(params: cli::Commit, config: Config) {
     let git = match Git::from_cwd() {
         Ok(git) => git,
@@ -31,25 +43,22 @@ pub fn commit(params: cli::Commit, config: Config) {
         }
     };
 
-    enum Stage {
-        Files,
-        Type,
-        Scope(String),
-        Message(String, Option<String>),
-        Complete(String, Option<String>, String),
+    if let Some(ty) = &params.ty {
+        if let Err(err) = config.check_custom_type(ty) {
+            eprintln!("Can't use the type \"{}\": {}", ty, err);
+            std::process::exit(1);
+        }
     }
 
-    let mut stage = Stage::Type;
+    let mut show_files = false;
 
-    let git_status = git.status().ok();
-
-    if let Some(ref git_status) = git_status {
+    if let Ok(git_status) = git.status() {
         let any_staged = git_status.any_staged();
         let any_unstaged = git_status.any_unstaged();
 
         if !any_staged && any_unstaged {
             if params.git_args.is_empty() {
-                stage = Stage::Files;
+                show_files = true;
             }
         } else if !any_staged {
             eprintln!("No changes to commit.");
@@ -57,40 +66,208 @@ pub fn commit(params: cli::Commit, config: Config) {
         }
     }
 
+    let (commit, commit_files) = match prompt(&params, &config, &git, show_files, true) {
+        Some(result) => result,
+        None => std::process::exit(0),
+    };
+
+    if let Some(commit_files) = commit_files {
+        let _r = git.add(commit_files).status();
+    }
+
+    let git_message = commit.build_message();
+
+    match git.commit(&git_message, params.git_args).status() {
+        Ok(status) if status.success() => println!("Commit successful."),
+        Ok(status) => {
+            match status.code() {
+                Some(code) => {
+                    eprintln!("Commit command failed with {}", code);
+                    std::process::exit(code);
+                }
+                None => {
+                    eprintln!("Commit command failed with no status. Was likely killed by another process.");
+                    std::process::exit(1);
+                }
+            }
+        }
+        Err(err) => {
+            eprintln!(
+                "Failed to run git. This is the best error I have:\n{:?}",
+                err
+            );
+            std::process::exit(1);
+        }
+    };
+}
+
+/// Scopes from recent history and the config, best first. A repo without commits just
+/// gets the configured scopes.
+fn suggest_scopes(config: &Config, git: &Git) -> Vec<String> {
+    let items: Vec<_> = match git.log_parsed(["-500", "--no-merges"]) {
+        Ok(logs) => logs.filter_map(Result::ok).collect(),
+        Err(_) => vec![],
+    };
+
+    scopes::suggest(&items, &config.scopes)
+}
+
+/// Scopes from the `scope_paths` config for the files chosen in the files prompt, or
+/// otherwise the staged files.
+fn infer_scopes(config: &Config, git: &Git, commit_files: Option<&Vec<String>>) -> Vec<String> {
+    if config.scope_paths.is_empty() {
+        return vec![];
+    }
+
+    match commit_files {
+        Some(files) => scopes::infer(&config.scope_paths, files.iter().map(String::as_str)),
+        None => match git.status() {
+            Ok(status) => scopes::infer(&config.scope_paths, status.staged_files()),
+            Err(_) => vec![],
+        },
+    }
+}
+
+/// Replaces the body of `commit` with one written in the user's editor. If that fails, the
+/// error is shown and the body is left as it was.
+fn edit_body(git: &Git, commit: &mut Commit, commit_files: Option<&Vec<String>>) {
+    let result = match commit_files {
+        Some(files) => editor::edit_body(git, commit, files.iter().map(String::as_str)),
+        None => match git.status() {
+            Ok(status) => editor::edit_body(git, commit, status.staged_files()),
+            Err(err) => Err(err),
+        },
+    };
+
+    match result {
+        Ok(body) => commit.body = Some(body).filter(|body| !body.is_empty()),
+        Err(err) => eprintln!("Failed to edit the body: {}", err),
+    }
+}
+
+/// Clears the `lines` printed by the scope prompt, so it can be shown again.
+fn clear_scope_lines(lines: &mut u16) {
+    let mut stderr = std::io::stderr();
+    ct::queue!(
+        stderr,
+        cursor::MoveUp(std::mem::take(lines)),
+        terminal::Clear(terminal::ClearType::FromCursorDown)
+    )
+    .unwrap();
+}
+
+/// The prompts that were shown, which the review can return to.
+fn review_edits(params: &cli::Commit, files_shown: bool) -> Vec<prompt::ReviewEdit> {
+    let mut edits = vec![];
+    if files_shown {
+        edits.push(prompt::ReviewEdit::Files);
+    }
+    if params.ty.is_none() {
+        edits.push(prompt::ReviewEdit::Type);
+    }
+    if params.scope.is_none() {
+        edits.push(prompt::ReviewEdit::Scope);
+    }
+    if params.message.is_none() {
+        edits.push(prompt::ReviewEdit::Subject);
+        edits.push(prompt::ReviewEdit::Body);
+        edits.push(prompt::ReviewEdit::Footers);
+    }
+    edits
+}
+
+/// Runs the prompts for anything not already given in `params`, starting with the files prompt
+/// if `show_files` is set. Escaping from the type prompt returns to the files prompt when
+/// `allow_files` is set, and otherwise returns `None`, as does escaping from the files prompt.
+///
+/// The commit is then shown for review, unless every prompt was skipped or `review` is off
+/// in the config. A prompt chosen from the review is prefilled, and escaping it, or any
+/// prompt after it, returns to the review. Prompts confirmed before the escape keep their
+/// changes.
+///
+/// Returns the commit and, if the files prompt was used, the files to stage.
+pub fn prompt(
+    params: &cli::Commit,
+    config: &Config,
+    git: &Git,
+    show_files: bool,
+    allow_files: bool,
+) -> Option<(Commit, Option<Vec<String>>)> {
+    let mut stage = if show_files {
+        Stage::Files
+    } else {
+        Stage::Type
+    };
+
     let mut commit_files: Option<Vec<String>> = None;
 
     let mut escape_clear_lines = 0;
 
+    // Loaded the first time the scope prompt is shown
+    let mut scope_suggestions: Option<Vec<String>> = None;
+
+    // With --editor, the editor opens the first time the body prompt would be shown
+    let mut open_editor = params.editor;
+
+    // The commit so far, kept when the review goes back to the files, type or scope prompts
+    let mut draft: Option<Commit> = None;
+
+    // Set when a prompt is chosen from the review, so escaping returns there
+    let mut reviewing = false;
+
     loop {
         match stage {
             Stage::Files => {
-                commit_files = with_raw(|| {
-                    match prompt::FilesPrompt::new(&config, &git, git_status.clone().unwrap()).run()
-                    {
-                        prompt::FilesPromptResult::Files(files) => Some(files),
-                        prompt::FilesPromptResult::Terminate => exit(2),
-                        prompt::FilesPromptResult::Escape => exit(0),
-                    }
+                let status = git.status().expect("git status for the files prompt");
+                let mut files_prompt = prompt::FilesPrompt::new(config, git, status);
+                if let Some(ref files) = commit_files {
+                    files_prompt = files_prompt.prefill(files);
+                }
+                let files = with_raw(|| match files_prompt.run() {
+                    prompt::FilesPromptResult::Files(files) => Some(files),
+                    prompt::FilesPromptResult::Terminate => exit(2),
+                    prompt::FilesPromptResult::Escape => None,
                 });
 
+                match files {
+                    Some(files) => commit_files = Some(files),
+                    None if reviewing => {
+                        stage = Stage::Review(draft.take().expect("the commit under review"));
+                        continue;
+                    }
+                    None => return None,
+                }
+
                 stage = Stage::Type;
             }
             Stage::Type => {
                 let ty = match params.ty {
                     Some(ref ty) => Some(ty.to_string()),
-                    None => with_raw(|| match prompt::TypePrompt::new(&config).run() {
-                        prompt::TypePromptResult::Type(ty) => Some(ty),
-                        prompt::TypePromptResult::Terminate => exit(2),
-                        prompt::TypePromptResult::Escape => None,
-                    }),
+                    None => {
+                        let mut type_prompt = prompt::TypePrompt::new(config);
+                        if let Some(ref draft) = draft {
+                            type_prompt = type_prompt.prefill(&draft.ty);
+                        }
+
+                        with_raw(|| match type_prompt.run() {
+                            prompt::TypePromptResult::Type(ty) => Some(ty),
+                            prompt::TypePromptResult::Terminate => exit(2),
+                            prompt::TypePromptResult::Escape => None,
+                        })
+                    }
                 };
 
                 let ty = match ty {
                     Some(s) => s,
-                    None => {
+                    None if reviewing => {
+                        stage = Stage::Review(draft.take().expect("the commit under review"));
+                        continue;
+                    }
+                    None if allow_files => {
                         stage = Stage::Files;
                         continue;
                     }
+                    None => return None,
                 };
 
                 stage = Stage::Scope(ty);
@@ -98,84 +275,203 @@ pub fn commit(params: cli::Commit, config: Config) {
             Stage::Scope(ty) => {
                 let scope = match params.scope {
                     Some(ref scope) => Some((Some(scope.to_string()), 0)),
-                    None => with_raw(|| match prompt::ScopePrompt::new(&config, &ty).run() {
-                        prompt::ScopePromptResult::Scope(scope, lines) => Some((scope, lines)),
-                        prompt::ScopePromptResult::Terminate => exit(2),
-                        prompt::ScopePromptResult::Escape => None,
-                    }),
+                    None => {
+                        let history =
+                            scope_suggestions.get_or_insert_with(|| suggest_scopes(config, git));
+
+                        // Scopes inferred from the files come first, and one that all the
+                        // mapped files agree on is filled in.
+                        let inferred = infer_scopes(config, git, commit_files.as_ref());
+                        let suggestions: Vec<String> = inferred
+                            .iter()
+                            .chain(history.iter().filter(|scope| !inferred.contains(scope)))
+                            .cloned()
+                            .collect();
+
+                        let mut scope_prompt = prompt::ScopePrompt::new(config, &ty, &suggestions);
+                        match draft.as_ref().and_then(|draft| draft.scope.as_ref()) {
+                            Some(scope) => scope_prompt = scope_prompt.prefill(scope),
+                            None => {
+                                if let [scope] = inferred.as_slice() {
+                                    scope_prompt = scope_prompt.prefill(scope);
+                                }
+                            }
+                        }
+
+                        with_raw(|| match scope_prompt.run() {
+                            prompt::ScopePromptResult::Scope(scope, lines) => Some((scope, lines)),
+                            prompt::ScopePromptResult::Terminate => exit(2),
+                            prompt::ScopePromptResult::Escape => None,
+                        })
+                    }
                 };
 
                 let (scope, lines) = match scope {
                     Some(t) => t,
+                    None if reviewing => {
+                        let draft = draft.take().expect("the commit under review");
+                        stage = Stage::Review(Commit { ty, ..draft });
+                        continue;
+                    }
                     None => {
                         stage = Stage::Type;
                         continue;
                     }
                 };
 
-                stage = Stage::Message(ty, scope);
+                stage = Stage::Subject(match draft.take() {
+                    Some(draft) => Commit { ty, scope, ..draft },
+                    None => Commit {
+                        ty,
+                        scope,
+                        subject: String::new(),
+                        body: None,
+                        breaking: params.breaking || params.breaking_change.is_some(),
+                        footers: params
+                            .breaking_change
+                            .iter()
+                            .map(Footer::breaking_change)
+                            .collect(),
+                    },
+                });
                 escape_clear_lines = lines as u16;
             }
-            Stage::Message(ty, scope) => {
-                let message = match params.message {
-                    Some(ref message) => Some(message.to_string()),
-                    None => with_raw(|| match prompt::MessagePrompt::new(&config).run() {
-                        prompt::MessagePromptResult::Message(message) => Some(message),
-                        prompt::MessagePromptResult::Terminate => exit(2),
-                        prompt::MessagePromptResult::Escape => None,
-                    }),
-                };
+            Stage::Subject(mut commit) => {
+                // A message from the command line skips the rest of the prompts, with any
+                // lines after the first becoming the body.
+                if let Some(ref message) = params.message {
+                    let mut lines = message.splitn(2, '\n');
+                    commit.subject = lines.next().unwrap_or_default().trim().to_string();
+                    commit.body = lines.next().map(String::from);
 
-                let message = match message {
-                    Some(s) => s,
+                    stage = Stage::Review(commit);
+                    continue;
+                }
+
+                let prefix = commit.prefix();
+                let subject_prompt =
+                    prompt::SubjectPrompt::new(config, &prefix).prefill(&commit.subject);
+                let subject = with_raw(|| match subject_prompt.run() {
+                    prompt::SubjectPromptResult::Subject(subject) => Some(subject),
+                    prompt::SubjectPromptResult::Terminate => exit(2),
+                    prompt::SubjectPromptResult::Escape => None,
+                });
+
+                match subject {
+                    Some(subject) => {
+                        commit.subject = subject;
+                        stage = Stage::Body(commit);
+                    }
+                    None if reviewing => stage = Stage::Review(commit),
                     None => {
-                        stage = Stage::Scope(ty);
+                        stage = Stage::Scope(commit.ty);
+                        clear_scope_lines(&mut escape_clear_lines);
+                    }
+                }
+            }
+            Stage::Body(mut commit) => {
+                if open_editor {
+                    open_editor = false;
+                    edit_body(git, &mut commit, commit_files.as_ref());
+                }
 
-                        let mut stderr = std::io::stderr();
-                        ct::queue!(
-                            stderr,
-                            cursor::MoveUp(escape_clear_lines),
-                            terminal::Clear(terminal::ClearType::FromCursorDown)
-                        )
-                        .unwrap();
+                let body_prompt = prompt::MessagePrompt::new(config)
+                    .prefill(commit.body.as_deref().unwrap_or(""));
 
-                        continue;
+                stage = match with_raw(|| body_prompt.run()) {
+                    prompt::MessagePromptResult::Message(body) => {
+                        commit.body = Some(body).filter(|body| !body.is_empty());
+                        Stage::Footers(commit)
+                    }
+                    prompt::MessagePromptResult::Editor(body) => {
+                        commit.body = Some(body);
+                        open_editor = true;
+                        Stage::Body(commit)
                     }
+                    prompt::MessagePromptResult::Terminate => return exit(2),
+                    prompt::MessagePromptResult::Escape if reviewing => Stage::Review(commit),
+                    prompt::MessagePromptResult::Escape => Stage::Subject(commit),
                 };
-
-                stage = Stage::Complete(ty, scope, message);
             }
-            Stage::Complete(ty, scope, message) => {
-                if let Some(commit_files) = commit_files {
-                    let _r = git.add(commit_files).status();
-                }
+            Stage::Footers(mut commit) => {
+                let footer_prompt = prompt::FooterPrompt::new(commit.footers.clone());
+                let footers = with_raw(|| match footer_prompt.run() {
+                    prompt::FooterPromptResult::Footers(footers) => Some(footers),
+                    prompt::FooterPromptResult::Terminate => exit(2),
+                    prompt::FooterPromptResult::Escape => None,
+                });
 
-                let commit = Commit { ty, scope, message };
+                stage = match footers {
+                    Some(footers) => {
+                        commit.footers = footers;
+                        Stage::Review(commit)
+                    }
+                    None if reviewing => Stage::Review(commit),
+                    None => Stage::Body(commit),
+                };
+            }
+            Stage::Review(commit) => {
+                reviewing = false;
 
-                let git_message = commit.build_message();
+                let edits = review_edits(params, allow_files && commit_files.is_some());
+                if !config.review || edits.is_empty() {
+                    stage = Stage::Complete(commit);
+                    continue;
+                }
 
-                match git.commit(&git_message, params.git_args).status() {
-                    Ok(status) if status.success() => println!("Commit successful."),
-                    Ok(status) => match status.code() {
-                        Some(code) => {
-                            eprintln!("Commit command failed with {}", code);
-                            std::process::exit(code);
-                        }
-                        None => {
-                            eprintln!("Commit command failed with no status. Was likely killed by another process.");
-                            std::process::exit(1);
-                        }
+                let files = match commit_files {
+                    Some(ref files) => files.clone(),
+                    None => match git.status() {
+                        Ok(status) => status.staged_files().map(String::from).collect(),
+                        Err(_) => vec![],
                     },
-                    Err(err) => {
-                        eprintln!(
-                            "Failed to run git. This is the best error I have:\n{:?}",
-                            err
-                        );
-                        std::process::exit(1);
+                };
+                // Escape goes back to the last prompt that was shown
+                let last = edits.last().copied();
+                let review_prompt = prompt::ReviewPrompt::new(config, &commit)
+                    .files(&files, commit_files.is_some())
+                    .git_args(&params.git_args)
+                    .edits(edits);
+
+                // Going back with Escape walks back through the prompts as usual
+                let edit = match with_raw(|| review_prompt.run()) {
+                    prompt::ReviewPromptResult::Commit => {
+                        stage = Stage::Complete(commit);
+                        continue;
                     }
+                    prompt::ReviewPromptResult::Edit(edit) => {
+                        reviewing = true;
+                        edit
+                    }
+                    prompt::ReviewPromptResult::Escape => last.expect("a prompt was shown"),
+                    prompt::ReviewPromptResult::Abort => return None,
+                    prompt::ReviewPromptResult::Terminate => return exit(2),
                 };
 
-                return;
+                stage = match edit {
+                    prompt::ReviewEdit::Files => {
+                        clear_scope_lines(&mut escape_clear_lines);
+                        draft = Some(commit);
+                        Stage::Files
+                    }
+                    prompt::ReviewEdit::Type => {
+                        clear_scope_lines(&mut escape_clear_lines);
+                        draft = Some(commit);
+                        Stage::Type
+                    }
+                    prompt::ReviewEdit::Scope => {
+                        clear_scope_lines(&mut escape_clear_lines);
+                        let ty = commit.ty.clone();
+                        draft = Some(commit);
+                        Stage::Scope(ty)
+                    }
+                    prompt::ReviewEdit::Subject => Stage::Subject(commit),
+                    prompt::ReviewEdit::Body => Stage::Body(commit),
+                    prompt::ReviewEdit::Footers => Stage::Footers(commit),
+                };
+            }
+            Stage::Complete(commit) => {
+                return Some((commit, commit_files));
             }
         }
     }
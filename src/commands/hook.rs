@@ -0,0 +1,302 @@
+use super::commit::prompt;
+use crate::cli;
+use glint::{Config, Git};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const HOOKS: &[&str] = &["commit-msg", "prepare-commit-msg"];
+
+/// Identifies hook scripts written by glint, so we never overwrite or remove anything else.
+const MARKER: &str = "# Installed by glint.";
+
+/// Suffix for a pre-existing hook that glint's hook runs before itself.
+const CHAINED_SUFFIX: &str = ".pre-glint";
+
+pub fn hook(params: cli::Hook, config: Config) {
+    let result = match params {
+        cli::Hook::Install { hooks } => {
+            let hooks = hook_names(hooks);
+            hooks_dir().and_then(|dir| install(&dir, &glint_path(), &hooks))
+        }
+        cli::Hook::Uninstall { hooks } => {
+            let hooks = hook_names(hooks);
+            hooks_dir().and_then(|dir| uninstall(&dir, &hooks))
+        }
+        cli::Hook::Run { hook, args } => {
+            run(&hook, args, config);
+            Ok(())
+        }
+    };
+
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
+
+fn hook_names(hooks: Vec<String>) -> Vec<String> {
+    if hooks.is_empty() {
+        return HOOKS.iter().map(|&hook| hook.to_string()).collect();
+    }
+
+    for hook in hooks.iter() {
+        if !HOOKS.contains(&hook.as_str()) {
+            eprintln!(
+                "Unsupported hook '{}'. Expected one of: {}",
+                hook,
+                HOOKS.join(", ")
+            );
+            std::process::exit(1);
+        }
+    }
+
+    hooks
+}
+
+fn hooks_dir() -> io::Result<PathBuf> {
+    let git = match Git::from_cwd() {
+        Ok(git) => git,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+
+    git.hooks_dir()
+}
+
+fn is_glint_hook(path: &Path) -> bool {
+    fs::read_to_string(path)
+        .map(|text| text.contains(MARKER))
+        .unwrap_or(false)
+}
+
+fn chained_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(CHAINED_SUFFIX);
+    path.with_file_name(name)
+}
+
+/// The glint the hooks run, which is this one.
+fn glint_path() -> String {
+    std::env::current_exe()
+        .map(|path| path.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| "glint".into())
+}
+
+fn script(glint: &str, hook: &str) -> String {
+    format!(
+        r#"#!/bin/sh
+{marker} Remove with `glint hook uninstall`.
+chained="$0{suffix}"
+if [ -x "$chained" ]; then
+    "$chained" "$@" || exit $?
+fi
+exec '{glint}' hook run {hook} "$@"
+"#,
+        marker = MARKER,
+        suffix = CHAINED_SUFFIX,
+        glint = glint.replace('\'', r"'\''"),
+        hook = hook,
+    )
+}
+
+fn install(dir: &Path, glint: &str, hooks: &[String]) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+
+    for hook in hooks {
+        let path = dir.join(hook);
+
+        if path.exists() && !is_glint_hook(&path) {
+            let chained = chained_path(&path);
+            if chained.exists() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!(
+                        "Can't keep the existing {} hook because {} already exists.",
+                        hook,
+                        chained.display()
+                    ),
+                ));
+            }
+
+            fs::rename(&path, &chained)?;
+            println!("Moved the existing hook to {}", chained.display());
+        }
+
+        fs::write(&path, script(glint, hook))?;
+        set_executable(&path)?;
+        println!("Installed {}", path.display());
+    }
+
+    Ok(())
+}
+
+fn uninstall(dir: &Path, hooks: &[String]) -> io::Result<()> {
+    for hook in hooks {
+        let path = dir.join(hook);
+
+        if !is_glint_hook(&path) {
+            continue;
+        }
+
+        fs::remove_file(&path)?;
+        println!("Removed {}", path.display());
+
+        let chained = chained_path(&path);
+        if chained.exists() {
+            fs::rename(&chained, &path)?;
+            println!("Restored the previous hook to {}", path.display());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn set_executable(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_mode(permissions.mode() | 0o755);
+    fs::set_permissions(path, permissions)
+}
+
+#[cfg(not(unix))]
+fn set_executable(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Whether there's a terminal to prompt on, even though git doesn't give hooks a stdin.
+#[cfg(unix)]
+fn has_tty() -> bool {
+    fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .is_ok()
+}
+
+#[cfg(not(unix))]
+fn has_tty() -> bool {
+    false
+}
+
+fn run(hook: &str, args: Vec<String>, config: Config) {
+    let mut args = args.into_iter();
+    let file = match args.next() {
+        Some(file) => PathBuf::from(file),
+        None => {
+            eprintln!("The {} hook requires the message file argument.", hook);
+            std::process::exit(1);
+        }
+    };
+
+    match hook {
+        "commit-msg" => {
+            let params = cli::Lint {
+                file: Some(file),
+                range: None,
+            };
+            super::lint(params, config);
+        }
+        "prepare-commit-msg" => {
+            // A source means the message came from -m, -F, a template, a merge, etc.
+            // Only prompt for a plain `git commit`, and only when someone can answer.
+            let has_source = args.next().filter(|source| !source.is_empty()).is_some();
+            if has_source || !has_tty() {
+                return;
+            }
+
+            let git = match Git::from_cwd() {
+                Ok(git) => git,
+                Err(err) => {
+                    eprintln!("{}", err);
+                    std::process::exit(1);
+                }
+            };
+
+            // Escaping out of the prompts leaves the message to the editor, as if glint wasn't
+            // there.
+            let commit = match prompt(&cli::Commit::default(), &config, &git, false, false) {
+                Some((commit, _)) => commit,
+                None => return,
+            };
+
+            let existing = fs::read_to_string(&file).unwrap_or_default();
+            let message = format!("{}\n{}", commit.build_message(), existing);
+            if let Err(err) = fs::write(&file, message) {
+                eprintln!("Failed to write {}: {}", file.display(), err);
+                std::process::exit(1);
+            }
+        }
+        _ => {
+            eprintln!(
+                "Unsupported hook '{}'. Expected one of: {}",
+                hook,
+                HOOKS.join(", ")
+            );
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{chained_path, hook_names, install, script, uninstall, HOOKS, MARKER};
+    use std::path::Path;
+    use std::{env, fs, process};
+
+    #[test]
+    fn names() {
+        assert_eq!(hook_names(vec![]), HOOKS);
+        assert_eq!(hook_names(vec!["commit-msg".into()]), vec!["commit-msg"]);
+        assert_eq!(
+            chained_path(Path::new(".git/hooks/commit-msg")),
+            Path::new(".git/hooks/commit-msg.pre-glint")
+        );
+    }
+
+    #[test]
+    fn script_runs_chained_hook_first() {
+        let text = script("/opt/it's/glint", "commit-msg");
+        assert!(text.starts_with("#!/bin/sh\n"));
+        assert!(text.contains(MARKER));
+        assert!(text.contains("chained=\"$0.pre-glint\"\n"));
+        assert!(text.contains("\"$chained\" \"$@\" || exit $?\n"));
+        assert!(text.ends_with("exec '/opt/it'\\''s/glint' hook run commit-msg \"$@\"\n"));
+    }
+
+    #[test]
+    fn chains_existing_hook() {
+        let dir = env::temp_dir().join(format!("glint-hooks-{}", process::id()));
+        let hooks = vec!["commit-msg".to_string()];
+        let hook = dir.join("commit-msg");
+        let chained = dir.join("commit-msg.pre-glint");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(&hook, "#!/bin/sh\nexit 0\n").unwrap();
+
+        install(&dir, "glint", &hooks).unwrap();
+        assert_eq!(fs::read_to_string(&chained).unwrap(), "#!/bin/sh\nexit 0\n");
+        assert_eq!(
+            fs::read_to_string(&hook).unwrap(),
+            script("glint", "commit-msg")
+        );
+
+        // Installing again replaces glint's own hook, and keeps the chained one
+        install(&dir, "glint", &hooks).unwrap();
+        assert_eq!(fs::read_to_string(&chained).unwrap(), "#!/bin/sh\nexit 0\n");
+
+        uninstall(&dir, &hooks).unwrap();
+        assert_eq!(fs::read_to_string(&hook).unwrap(), "#!/bin/sh\nexit 0\n");
+        assert!(!chained.exists());
+
+        // A chained hook that's already there is never overwritten
+        fs::write(&chained, "other").unwrap();
+        assert!(install(&dir, "glint", &hooks).is_err());
+        assert_eq!(fs::read_to_string(&hook).unwrap(), "#!/bin/sh\nexit 0\n");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,135 @@
+use crate::cli;
+use crossterm::{
+    self as ct,
+    style::{Color, Print, SetForegroundColor as SetFg},
+};
+use glint::lint::{self, Severity};
+use glint::{Config, Git};
+use std::io::{self, Read as _Read, Write as _Write};
+
+struct Message {
+    commit: Option<String>,
+    text: String,
+}
+
+fn read_messages(params: cli::Lint) -> io::Result<Vec<Message>> {
+    let messages = match (params.file, params.range) {
+        (Some(file), _) => vec![Message {
+            commit: None,
+            text: lint::strip_comments(&std::fs::read_to_string(file)?),
+        }],
+        (None, Some(range)) => {
+            let git = match Git::from_cwd() {
+                Ok(git) => git,
+                Err(err) => {
+                    eprintln!("{}", err);
+                    std::process::exit(1);
+                }
+            };
+
+            git.log_parsed(&[range])?
+                .map(|item| {
+                    item.map(|item| Message {
+                        commit: Some(item.commit),
+                        text: item.message,
+                    })
+                })
+                .collect::<io::Result<_>>()?
+        }
+        (None, None) => {
+            let mut text = String::new();
+            io::stdin().read_to_string(&mut text)?;
+            vec![Message {
+                commit: None,
+                text: lint::strip_comments(&text),
+            }]
+        }
+    };
+
+    Ok(messages)
+}
+
+pub fn lint(params: cli::Lint, config: Config) {
+    let messages = match read_messages(params) {
+        Ok(messages) => messages,
+        Err(err) => {
+            eprintln!("Failed to read commit message: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let mut errors = 0;
+    let mut warnings = 0;
+
+    let stdout = &mut io::stdout();
+    for message in messages {
+        if lint::is_ignored(&message.text) {
+            continue;
+        }
+
+        let diagnostics = lint::lint(&message.text, &config);
+        if diagnostics.is_empty() {
+            continue;
+        }
+
+        if let Some(ref commit) = message.commit {
+            ct::queue!(
+                stdout,
+                SetFg(Color::Yellow),
+                Print(&commit[..8]),
+                Print(" ")
+            )
+            .unwrap();
+        }
+        ct::queue!(
+            stdout,
+            SetFg(Color::Reset),
+            Print(message.text.lines().next().unwrap_or("")),
+            Print("\n")
+        )
+        .unwrap();
+
+        for diagnostic in diagnostics {
+            let color = match diagnostic.severity {
+                Severity::Error => {
+                    errors += 1;
+                    Color::Red
+                }
+                Severity::Warning => {
+                    warnings += 1;
+                    Color::Yellow
+                }
+            };
+
+            ct::queue!(
+                stdout,
+                Print("  "),
+                SetFg(color),
+                Print(diagnostic.severity),
+                SetFg(Color::Reset),
+                Print(format!(" {} ", diagnostic.message)),
+                SetFg(Color::DarkGrey),
+                Print(format!("[{}]", diagnostic.rule)),
+                SetFg(Color::Reset),
+                Print("\n")
+            )
+            .unwrap();
+        }
+    }
+
+    if errors + warnings > 0 {
+        ct::queue!(
+            stdout,
+            Print(format!(
+                "\nFound {} error(s), {} warning(s)\n",
+                errors, warnings
+            ))
+        )
+        .unwrap();
+    }
+    stdout.flush().unwrap();
+
+    if errors > 0 {
+        std::process::exit(1);
+    }
+}
@@ -3,11 +3,163 @@ use crossterm::{
     self as ct,
     style::{Color, Print, SetForegroundColor as SetFg},
 };
+use glint::filter::{Filter, LogFilter};
 use glint::string;
-use glint::{Config, Git};
+use glint::{Config, FileChange, Git, LogItem, Signature};
+use serde::Serialize;
 use std::io::Write as _Write;
 use std::{io, iter};
 
+#[derive(Serialize)]
+struct JsonSignature<'a> {
+    name: &'a str,
+    email: &'a str,
+    timestamp: i64,
+    /// Minutes east of UTC, e.g. 330 for `+0530`.
+    offset_mins: i32,
+}
+
+impl<'a> JsonSignature<'a> {
+    fn new(signature: &'a Signature) -> Self {
+        JsonSignature {
+            name: &signature.name,
+            email: &signature.email,
+            timestamp: signature.epoch_secs,
+            offset_mins: signature.offset_mins,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonFile<'a> {
+    status: char,
+    path: &'a str,
+    /// The source of a rename or copy.
+    old_path: Option<&'a str>,
+    score: Option<u8>,
+    old_mode: &'a str,
+    new_mode: &'a str,
+    old_blob: &'a str,
+    new_blob: &'a str,
+}
+
+impl<'a> JsonFile<'a> {
+    fn new(file: &'a FileChange) -> Self {
+        JsonFile {
+            status: file.status,
+            path: &file.path,
+            old_path: file.old_path.as_deref(),
+            score: file.score,
+            old_mode: &file.old_mode,
+            new_mode: &file.new_mode,
+            old_blob: &file.old_blob,
+            new_blob: &file.new_blob,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonFooter<'a> {
+    token: &'a str,
+    value: &'a str,
+}
+
+/// A commit in `--json` and `--ndjson` output. `type` is null for commits that aren't
+/// conventional, in which case the first line is the subject and the rest is the body.
+#[derive(Serialize)]
+struct JsonItem<'a> {
+    commit: &'a str,
+    /// Committer date, in seconds since the unix epoch.
+    timestamp: i64,
+    tree: &'a str,
+    parents: &'a [String],
+    author: JsonSignature<'a>,
+    committer: JsonSignature<'a>,
+    #[serde(rename = "type")]
+    ty: Option<&'a str>,
+    scope: Option<&'a str>,
+    subject: &'a str,
+    body: Option<&'a str>,
+    breaking: bool,
+    footers: Vec<JsonFooter<'a>>,
+    files: Vec<JsonFile<'a>>,
+}
+
+impl<'a> JsonItem<'a> {
+    fn new(log: &'a LogItem) -> Self {
+        match log.as_conventional() {
+            Some(conv) => JsonItem {
+                commit: &log.commit,
+                timestamp: log.committer.epoch_secs,
+                tree: &log.tree,
+                parents: &log.parents,
+                author: JsonSignature::new(&log.author),
+                committer: JsonSignature::new(&log.committer),
+                ty: Some(conv.ty),
+                scope: conv.scope,
+                subject: conv.subject,
+                body: conv.body,
+                breaking: conv.breaking,
+                footers: conv
+                    .footers
+                    .iter()
+                    .map(|footer| JsonFooter {
+                        token: footer.token,
+                        value: footer.value,
+                    })
+                    .collect(),
+                files: log.files.iter().map(JsonFile::new).collect(),
+            },
+            None => {
+                let mut parts = log.message.splitn(2, '\n');
+                JsonItem {
+                    commit: &log.commit,
+                    timestamp: log.committer.epoch_secs,
+                    tree: &log.tree,
+                    parents: &log.parents,
+                    author: JsonSignature::new(&log.author),
+                    committer: JsonSignature::new(&log.committer),
+                    ty: None,
+                    scope: None,
+                    subject: parts.next().unwrap_or(""),
+                    body: parts.next().map(str::trim).filter(|body| !body.is_empty()),
+                    breaking: false,
+                    footers: vec![],
+                    files: log.files.iter().map(JsonFile::new).collect(),
+                }
+            }
+        }
+    }
+}
+
+/// Streams a JSON array, so the first commits are printed before git has finished.
+fn print_json(logs: impl Iterator<Item = io::Result<LogItem>>) -> io::Result<()> {
+    let stdout = io::stdout();
+    let stdout = &mut stdout.lock();
+
+    write!(stdout, "[")?;
+    for (i, log) in logs.enumerate() {
+        let log = log?;
+        if i > 0 {
+            write!(stdout, ",")?;
+        }
+        serde_json::to_writer(&mut *stdout, &JsonItem::new(&log))?;
+    }
+    writeln!(stdout, "]")?;
+    stdout.flush()
+}
+
+fn print_ndjson(logs: impl Iterator<Item = io::Result<LogItem>>) -> io::Result<()> {
+    let stdout = io::stdout();
+    let stdout = &mut stdout.lock();
+
+    for log in logs {
+        serde_json::to_writer(&mut *stdout, &JsonItem::new(&log?))?;
+        writeln!(stdout)?;
+    }
+    stdout.flush()
+}
+
 pub fn log(params: cli::Log, _config: Config) {
     let git = match Git::from_cwd() {
         Ok(git) => git,
@@ -16,29 +168,85 @@ pub fn log(params: cli::Log, _config: Config) {
             std::process::exit(1);
         }
     };
-    let size = ct::terminal::size().expect("terminal size");
+    let machine_readable = params.json || params.ndjson;
+
+    // Output may be piped somewhere without a terminal, e.g. with --json
+    let size = ct::terminal::size().unwrap_or((80, 24));
 
     let width = std::cmp::max(size.0, 60) as usize;
-    let height = params
-        .num
-        .unwrap_or_else(|| std::cmp::max(size.1, 15) as usize);
-    let count_arg = format!("-{}", height);
-    let args = iter::once(&count_arg).chain(params.git_args.iter());
-    let logs = git.log_parsed(args).expect("parse logs");
+    let height = match params.num {
+        Some(num) => Some(num),
+        None if machine_readable => None,
+        None => Some(std::cmp::max(size.1, 15) as usize),
+    };
+    let filter = LogFilter {
+        ty: Filter::new(&params.ty),
+        scope: Filter::new(&params.scope),
+    };
+
+    // When filtering, read as much history as it takes to find enough matches, rather than
+    // asking git for a fixed number of commits.
+    let count_arg = height
+        .filter(|_| filter.is_empty())
+        .map(|height| format!("-{}", height));
+    let args = count_arg.iter().chain(params.git_args.iter());
+    let logs = match git.log_parsed(args) {
+        Ok(logs) => logs,
+        Err(err) => {
+            eprintln!("Failed to run git log: {}", err);
+            std::process::exit(1);
+        }
+    };
+    let logs = logs
+        .filter(|log| match log {
+            Ok(log) => filter.matches(log),
+            Err(_) => true,
+        })
+        .take(height.unwrap_or(usize::MAX));
+
+    let result = if params.json {
+        print_json(logs)
+    } else if params.ndjson {
+        print_ndjson(logs)
+    } else {
+        print_text(logs, width, params.debug)
+    };
+
+    match result {
+        Ok(()) => {}
+        // e.g. piped into `head`
+        Err(ref err) if err.kind() == io::ErrorKind::BrokenPipe => {}
+        Err(err) => {
+            eprintln!("Failed to read the git log: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
 
+fn print_text(
+    logs: impl Iterator<Item = io::Result<LogItem>>,
+    width: usize,
+    debug: bool,
+) -> io::Result<()> {
     let stdout = &mut io::stdout();
     for log in logs {
+        let log = log?;
         let conv = log.as_conventional();
 
         let scope = conv.clone().and_then(|c| c.scope.map(String::from));
+        let breaking = conv.as_ref().map(|c| c.breaking).unwrap_or(false);
         let ty = match conv {
             Some(ref conv) => conv.ty.to_string(),
+            None if log.is_merge() => "merge".to_string(),
             None => "unknown".to_string(),
         };
 
         let message = match conv {
-            Some(ref conv) => conv.message,
-            None => &log.message,
+            Some(ref conv) => iter::once(conv.subject)
+                .chain(conv.body)
+                .collect::<Vec<&str>>()
+                .join("\n"),
+            None => log.message.clone(),
         };
 
         let message = message
@@ -46,9 +254,16 @@ pub fn log(params: cli::Log, _config: Config) {
             .filter(|s| s.chars().any(|c| !c.is_whitespace()))
             .collect::<Vec<&str>>()
             .join(" ⏎");
-        let message = string::split_at(&message, width).0.to_string();
+        // The hash and a space, then `type(scope)!: `
+        let prefix_width = 9
+            + string::width(&ty)
+            + scope.as_ref().map_or(0, |scope| string::width(scope) + 2)
+            + breaking as usize
+            + 2;
+        let message = string::truncate_with_ellipsis(&message, width.saturating_sub(prefix_width))
+            .into_owned();
 
-        if params.debug {
+        if debug {
             println!("----------\nItem: {:#?}\nas_conventional: {:#?}", log, conv);
         } else {
             ct::queue!(
@@ -70,9 +285,13 @@ pub fn log(params: cli::Log, _config: Config) {
                 }),
                 SetFg(Color::Grey),
                 Print(match scope {
-                    Some(_) => "): ",
-                    None => ": ",
+                    Some(_) => ")",
+                    None => "",
                 }),
+                SetFg(Color::Red),
+                Print(if breaking { "!" } else { "" }),
+                SetFg(Color::Grey),
+                Print(": "),
                 SetFg(Color::Reset),
                 Print(message),
                 Print("\n")
@@ -80,5 +299,5 @@ pub fn log(params: cli::Log, _config: Config) {
             .unwrap();
         }
     }
-    stdout.flush().unwrap();
+    stdout.flush()
 }
@@ -0,0 +1,147 @@
+use crate::cli;
+use crossterm::{
+    self as ct,
+    style::{Color, Print, SetForegroundColor as SetFg},
+};
+use glint::stats::{Count, Stats, StatsBuilder};
+use glint::{string, Config, Git};
+use std::io::{self, Write as _Write};
+
+const BAR_WIDTH: usize = 30;
+
+fn print_heading(stdout: &mut io::Stdout, heading: &str) {
+    ct::queue!(
+        stdout,
+        Print("\n"),
+        SetFg(Color::Magenta),
+        Print(heading),
+        SetFg(Color::Reset),
+        Print("\n")
+    )
+    .unwrap();
+}
+
+fn print_counts(stdout: &mut io::Stdout, indent: &str, counts: &[Count]) {
+    let name_width = counts.iter().map(|c| string::width(&c.name)).max();
+    let max = counts.iter().map(|c| c.count).max().unwrap_or(1);
+
+    for count in counts {
+        let bar = "█".repeat(std::cmp::max(1, count.count * BAR_WIDTH / max));
+
+        ct::queue!(
+            stdout,
+            Print(format!(
+                "{}{}{}  {:>5}  ",
+                indent,
+                count.name,
+                // Padded by hand, since format! counts chars rather than columns
+                " ".repeat(name_width.unwrap_or(0) - string::width(&count.name)),
+                count.count,
+            )),
+            SetFg(Color::Blue),
+            Print(bar),
+            SetFg(Color::Reset),
+            Print("\n")
+        )
+        .unwrap();
+    }
+}
+
+fn print_table(stats: &Stats) {
+    let stdout = &mut io::stdout();
+
+    ct::queue!(
+        stdout,
+        Print(format!(
+            "{} commits, {} ({:.1}%) not conventional\n",
+            stats.total,
+            stats.non_conventional,
+            stats.non_conventional_share * 100.0
+        ))
+    )
+    .unwrap();
+
+    let sections = [
+        ("By type", &stats.by_type),
+        ("By scope", &stats.by_scope),
+        ("By author", &stats.by_author),
+        ("By week", &stats.by_week),
+    ];
+    for (heading, counts) in sections.iter() {
+        if !counts.is_empty() {
+            print_heading(stdout, heading);
+            print_counts(stdout, "  ", counts);
+        }
+    }
+
+    if !stats.files_by_scope.is_empty() {
+        print_heading(stdout, "Most changed files by scope");
+        for scope in &stats.files_by_scope {
+            ct::queue!(
+                stdout,
+                SetFg(Color::Blue),
+                Print(format!("  {}", scope.scope)),
+                SetFg(Color::Reset),
+                Print("\n")
+            )
+            .unwrap();
+            print_counts(stdout, "    ", &scope.files);
+        }
+    }
+
+    stdout.flush().unwrap();
+}
+
+fn print_json(stats: &Stats) -> io::Result<()> {
+    let stdout = io::stdout();
+    let stdout = &mut stdout.lock();
+
+    serde_json::to_writer(&mut *stdout, stats)?;
+    writeln!(stdout)?;
+    stdout.flush()
+}
+
+pub fn stats(params: cli::Stats, _config: Config) {
+    let git = match Git::from_cwd() {
+        Ok(git) => git,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+
+    // Merge commits are left out, since they'd all count as not conventional
+    let mut args = vec!["--no-merges".to_string()];
+    args.extend(params.since.map(|since| format!("--since={}", since)));
+    args.extend(params.until.map(|until| format!("--until={}", until)));
+    args.extend(params.range);
+
+    let mut builder = StatsBuilder::default();
+    let result = git.log_parsed(&args).and_then(|logs| {
+        for log in logs {
+            builder.add(&log?);
+        }
+        Ok(())
+    });
+    if let Err(err) = result {
+        eprintln!("Failed to read the git log: {}", err);
+        std::process::exit(1);
+    }
+
+    let stats = builder.build(params.top_files);
+
+    if !params.json {
+        print_table(&stats);
+        return;
+    }
+
+    match print_json(&stats) {
+        Ok(()) => {}
+        // e.g. piped into `head`
+        Err(ref err) if err.kind() == io::ErrorKind::BrokenPipe => {}
+        Err(err) => {
+            eprintln!("Failed to write the stats: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
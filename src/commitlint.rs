@@ -1,24 +1,75 @@
 pub struct Commit {
     pub ty: String,
     pub scope: Option<String>,
-    pub message: String,
+    /// The rest of the first line, after `type(scope): `.
+    pub subject: String,
+    /// Paragraphs after the subject, already wrapped.
+    pub body: Option<String>,
+    /// Adds a `!` after the type/scope.
+    pub breaking: bool,
+    pub footers: Vec<Footer>,
 }
 
-impl Commit {
-    pub fn build_message(&self) -> String {
-        // This with_capacity is likely excessive
-        const PARENS: usize = 2;
-        const COLON: usize = 1;
-        const SPACE: usize = 1;
-        let len = self.ty.len()
-            + self.message.len()
-            + self.scope.as_ref().map(|s| s.len() + PARENS).unwrap_or(0)
-            + COLON
-            + SPACE;
+/// A `token: value` line at the end of the commit message, e.g. `BREAKING CHANGE: ...`
+/// or `Refs: #123`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Footer {
+    pub token: String,
+    pub value: String,
+}
+
+impl Footer {
+    pub fn new(token: impl Into<String>, value: impl Into<String>) -> Self {
+        Footer {
+            token: token.into(),
+            value: value.into(),
+        }
+    }
+
+    pub fn breaking_change(description: impl Into<String>) -> Self {
+        Footer::new("BREAKING CHANGE", description)
+    }
 
-        let mut s = String::with_capacity(len);
+    /// Parses a footer as typed in the footer prompt: `Token: value`, `Token #value`, or an
+    /// issue reference on its own like `#123`, which becomes `Refs: #123`.
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let line = line.trim();
+
+        if line.starts_with('#') {
+            return Ok(Footer::new("Refs", line));
+        }
 
-        s.push_str(&self.ty);
+        let (token, value) = match (line.find(": "), line.find(" #")) {
+            (Some(colon), Some(hash)) if hash < colon => (&line[..hash], &line[hash + 1..]),
+            (Some(colon), _) => (&line[..colon], &line[colon + 2..]),
+            (None, Some(hash)) => (&line[..hash], &line[hash + 1..]),
+            (None, None) => {
+                return Err("expected \"Token: value\", \"Token #issue\", or \"#issue\"".into())
+            }
+        };
+        let (token, value) = (token.trim(), value.trim());
+
+        if token.eq_ignore_ascii_case("BREAKING CHANGE") {
+            return Ok(Footer::breaking_change(value));
+        }
+        if token.is_empty() || value.is_empty() {
+            return Err("a footer needs a token and a value".into());
+        }
+        if token.contains(char::is_whitespace) {
+            return Err(format!(
+                "footer tokens use '-' instead of spaces, e.g. \"{}\"",
+                token.split_whitespace().collect::<Vec<_>>().join("-")
+            ));
+        }
+
+        Ok(Footer::new(token, value))
+    }
+}
+
+impl Commit {
+    /// The start of the header, up to the subject: `type(scope)!: `.
+    pub fn prefix(&self) -> String {
+        let mut s = self.ty.clone();
 
         if let Some(ref scope) = self.scope {
             s.push('(');
@@ -26,10 +77,81 @@ impl Commit {
             s.push(')');
         }
 
-        s.push(':');
-        s.push(' ');
-        s.push_str(&self.message);
+        if self.breaking {
+            s.push('!');
+        }
+
+        s.push_str(": ");
+        s
+    }
+
+    pub fn build_message(&self) -> String {
+        let mut s = self.prefix();
+        s.push_str(&self.subject);
+
+        let body = self.body.as_deref().map(|body| body.trim_matches('\n'));
+        if let Some(body) = body.filter(|body| !body.trim().is_empty()) {
+            s.push_str("\n\n");
+            s.push_str(body.trim_end());
+        }
+
+        if !self.footers.is_empty() {
+            s.push_str("\n\n");
+
+            for (i, footer) in self.footers.iter().enumerate() {
+                if i > 0 {
+                    s.push('\n');
+                }
+                s.push_str(&footer.token);
+                s.push_str(": ");
+                s.push_str(&footer.value);
+            }
+        }
 
         s
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{Commit, Footer};
+
+    #[test]
+    fn build_message() {
+        let mut commit = Commit {
+            ty: "feat".into(),
+            scope: Some("api".into()),
+            subject: "add things".into(),
+            body: Some("\nFirst paragraph.\n\nSecond.\n\n".into()),
+            breaking: true,
+            footers: vec![Footer::new("Refs", "#1"), Footer::breaking_change("gone")],
+        };
+        assert_eq!(
+            commit.build_message(),
+            "feat(api)!: add things\n\nFirst paragraph.\n\nSecond.\n\nRefs: #1\nBREAKING CHANGE: gone"
+        );
+
+        commit.body = Some("\n".into());
+        commit.footers.clear();
+        assert_eq!(commit.build_message(), "feat(api)!: add things");
+    }
+
+    #[test]
+    fn parse_footer() {
+        assert_eq!(Footer::parse("#12"), Ok(Footer::new("Refs", "#12")));
+        assert_eq!(
+            Footer::parse("Closes #12"),
+            Ok(Footer::new("Closes", "#12"))
+        );
+        assert_eq!(
+            Footer::parse("Reviewed-by: Ann <ann@example.com>"),
+            Ok(Footer::new("Reviewed-by", "Ann <ann@example.com>"))
+        );
+        assert_eq!(
+            Footer::parse("breaking change: no more x"),
+            Ok(Footer::breaking_change("no more x"))
+        );
+        assert!(Footer::parse("Reviewed by: Ann").is_err());
+        assert!(Footer::parse("just words").is_err());
+    }
+}
@@ -1,43 +1,498 @@
-use crate::Figlet;
+use crate::color;
+use crate::scopes::ScopePath;
+use crate::{Conventional, Figlet};
+use crossterm::style::Color;
+use serde::Deserialize;
+use std::convert::TryFrom;
+use std::env;
+use std::fmt;
+use std::fs;
 use std::io;
+use std::path::{Path, PathBuf};
+
+mod commitlintrc;
+
+/// File names checked in the repository root, in order of preference.
+const REPO_CONFIG_FILES: &[&str] = &[".glintrc", "glint.toml"];
 
 #[derive(Debug, Clone)]
 pub struct Config {
-    pub types: Vec<String>,
+    pub types: Vec<CommitType>,
+    /// Whether the type prompt submits as soon as the input matches a single type.
+    pub type_auto_submit: bool,
+    /// Whether a type that isn't in `types` may be used.
+    pub custom_types: bool,
+    /// Whether to show the message for review before committing.
+    pub review: bool,
+    /// Known scopes. Empty means any scope is accepted.
+    pub scopes: Vec<String>,
+    /// Mappings used to infer the scope from the files being committed, first match wins.
+    pub scope_paths: Vec<ScopePath>,
+    /// Maximum length of the first line of a commit message.
+    pub header_max_length: usize,
+    /// The column the body is wrapped at in the body prompt.
+    pub body_max_line_length: usize,
     pub figlet_file: Option<String>,
+    pub changelog: ChangelogConfig,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChangelogConfig {
+    /// Section titles by type, in the order they're rendered.
+    pub sections: Vec<ChangelogSection>,
+    /// Types left out of the changelog, unless the commit is a breaking change.
+    pub hidden: Vec<String>,
+    /// Link target for commit hashes, where `{hash}` is replaced by the full hash.
+    pub commit_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+pub struct ChangelogSection {
+    #[serde(rename = "type")]
+    pub ty: String,
+    pub title: String,
+}
+
+/// A type offered in the type prompt. In a config file this is either just the name, or a
+/// table with the other fields.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+#[serde(try_from = "CommitTypeFile")]
+pub struct CommitType {
+    pub name: String,
+    /// Explains when to use the type, shown next to it in the prompt.
+    pub description: Option<String>,
+    pub emoji: Option<String>,
+    /// Other names that find this type when filtering, e.g. "bug" for "fix".
+    pub aliases: Vec<String>,
+    pub color: Option<Color>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum CommitTypeFile {
+    Name(String),
+    Table {
+        name: String,
+        description: Option<String>,
+        emoji: Option<String>,
+        #[serde(default)]
+        aliases: Vec<String>,
+        color: Option<String>,
+    },
+}
+
+impl TryFrom<CommitTypeFile> for CommitType {
+    type Error = String;
+
+    fn try_from(file: CommitTypeFile) -> Result<Self, Self::Error> {
+        match file {
+            CommitTypeFile::Name(name) => Ok(CommitType::new(name)),
+            CommitTypeFile::Table {
+                name,
+                description,
+                emoji,
+                aliases,
+                color,
+            } => {
+                let color = match color {
+                    Some(name) => match color::from_name(&name) {
+                        Some(color) => Some(color),
+                        None => return Err(format!("unknown color \"{}\"", name)),
+                    },
+                    None => None,
+                };
+
+                Ok(CommitType {
+                    name,
+                    description,
+                    emoji,
+                    aliases,
+                    color,
+                })
+            }
+        }
+    }
+}
+
+impl CommitType {
+    pub fn new(name: impl Into<String>) -> Self {
+        CommitType {
+            name: name.into(),
+            description: None,
+            emoji: None,
+            aliases: vec![],
+            color: None,
+        }
+    }
+
+    fn with(mut self, description: &str, aliases: &[&str]) -> Self {
+        self.description = Some(description.into());
+        self.aliases = aliases.iter().map(|alias| alias.to_string()).collect();
+        self
+    }
+
+    /// Whether this is just a name, without any of the other fields.
+    fn is_bare(&self) -> bool {
+        self.description.is_none()
+            && self.emoji.is_none()
+            && self.aliases.is_empty()
+            && self.color.is_none()
+    }
+}
+
+/// One layer of configuration as it appears on disk. Every field is optional so that
+/// a layer only overrides what it specifies.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    types: Option<Vec<CommitType>>,
+    type_auto_submit: Option<bool>,
+    custom_types: Option<bool>,
+    review: Option<bool>,
+    scopes: Option<Vec<String>>,
+    scope_paths: Option<Vec<ScopePath>>,
+    header_max_length: Option<usize>,
+    body_max_line_length: Option<usize>,
+    figlet_file: Option<String>,
+    changelog: ChangelogFile,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ChangelogFile {
+    sections: Option<Vec<ChangelogSection>>,
+    hidden: Option<Vec<String>>,
+    commit_url: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, String),
 }
 
 impl Config {
+    /// The type named `name`, or with `name` as an alias.
+    pub fn find_type(&self, name: &str) -> Option<&CommitType> {
+        self.types.iter().find(|ty| ty.name == name).or_else(|| {
+            self.types
+                .iter()
+                .find(|ty| ty.aliases.iter().any(|a| a == name))
+        })
+    }
+
+    /// Checks that `ty` can be used as a type, either because it's listed in `types`, or
+    /// because custom types are allowed and it would make a header that parses.
+    pub fn check_custom_type(&self, ty: &str) -> Result<(), String> {
+        if self.type_names().any(|name| name == ty) {
+            Ok(())
+        } else if !self.custom_types {
+            Err(format!(
+                "custom types are disabled, use one of [{}]",
+                self.type_names().collect::<Vec<_>>().join(", ")
+            ))
+        } else if ty.is_empty() {
+            Err("the type may not be empty".into())
+        } else if Conventional::parse(&format!("{}: x", ty)).map(|conv| conv.ty) != Some(ty) {
+            Err("the type may only contain letters".into())
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn type_names(&self) -> impl Iterator<Item = &str> {
+        self.types.iter().map(|ty| ty.name.as_str())
+    }
+
     pub fn get_figlet(&self) -> Result<Figlet, io::Error> {
         match self.figlet_file {
             Some(ref figlet_file) => Figlet::from_file(figlet_file),
             None => Ok(Figlet::default()),
         }
     }
+
+    /// Builds the config from the defaults, then the user config file
+    /// (`$XDG_CONFIG_HOME/glint/config.toml`), then any static commitlint config in `repo_root`,
+    /// then the first of `.glintrc` or `glint.toml` found in `repo_root`. Later layers override
+    /// earlier ones field by field.
+    pub fn load(repo_root: Option<&Path>) -> Result<Self, ConfigError> {
+        let mut config = Config::default();
+
+        if let Some(path) = user_config_path().filter(|path| path.is_file()) {
+            config.merge_file(&path)?;
+        }
+
+        if let Some(repo_root) = repo_root {
+            if let Some((path, file)) = commitlintrc::load(repo_root)? {
+                config.merge(file, path.parent());
+            }
+
+            let repo_file = REPO_CONFIG_FILES
+                .iter()
+                .map(|name| repo_root.join(name))
+                .find(|path| path.is_file());
+
+            if let Some(path) = repo_file {
+                config.merge_file(&path)?;
+            }
+        }
+
+        Ok(config)
+    }
+
+    fn merge_file(&mut self, path: &Path) -> Result<(), ConfigError> {
+        let text = fs::read_to_string(path).map_err(|err| ConfigError::Io(path.into(), err))?;
+        let file: ConfigFile = toml::from_str(&text)
+            .map_err(|err| ConfigError::Parse(path.into(), err.to_string()))?;
+
+        self.merge(file, path.parent());
+
+        Ok(())
+    }
+
+    fn merge(&mut self, file: ConfigFile, base_dir: Option<&Path>) {
+        // A type given by name only, e.g. from a commitlint type-enum, keeps the description
+        // and other details of the type it replaces.
+        if let Some(types) = file.types {
+            let previous = std::mem::take(&mut self.types);
+            self.types = types
+                .into_iter()
+                .map(|ty| match previous.iter().find(|p| p.name == ty.name) {
+                    Some(previous) if ty.is_bare() => previous.clone(),
+                    _ => ty,
+                })
+                .collect();
+        }
+
+        if let Some(type_auto_submit) = file.type_auto_submit {
+            self.type_auto_submit = type_auto_submit;
+        }
+
+        if let Some(custom_types) = file.custom_types {
+            self.custom_types = custom_types;
+        }
+
+        if let Some(review) = file.review {
+            self.review = review;
+        }
+
+        if let Some(scopes) = file.scopes {
+            self.scopes = scopes;
+        }
+
+        if let Some(scope_paths) = file.scope_paths {
+            self.scope_paths = scope_paths;
+        }
+
+        if let Some(header_max_length) = file.header_max_length {
+            self.header_max_length = header_max_length;
+        }
+
+        if let Some(body_max_line_length) = file.body_max_line_length {
+            self.body_max_line_length = body_max_line_length;
+        }
+
+        // Relative figlet paths are resolved against the directory of the file that set them,
+        // so a repo config works regardless of where in the repo glint is run.
+        if let Some(figlet_file) = file.figlet_file {
+            let path = match base_dir {
+                Some(base_dir) => base_dir.join(figlet_file),
+                None => PathBuf::from(figlet_file),
+            };
+            self.figlet_file = Some(path.to_string_lossy().into_owned());
+        }
+
+        if let Some(sections) = file.changelog.sections {
+            self.changelog.sections = sections;
+        }
+
+        if let Some(hidden) = file.changelog.hidden {
+            self.changelog.hidden = hidden;
+        }
+
+        if let Some(commit_url) = file.changelog.commit_url {
+            self.changelog.commit_url = Some(commit_url);
+        }
+    }
+}
+
+fn user_config_path() -> Option<PathBuf> {
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+
+    Some(config_home.join("glint").join("config.toml"))
 }
 
 impl Default for Config {
     fn default() -> Self {
         Config {
             types: vec![
-                "build",
-                "ci",
-                "chore",
-                "docs",
-                "feat",
-                "fix",
-                "perf",
-                "refactor",
-                "revert",
-                "style",
-                "test",
-                "enhance",
-                "improvement",
+                CommitType::new("build").with(
+                    "Changes to the build system or external dependencies",
+                    &["deps"],
+                ),
+                CommitType::new("ci").with("Changes to CI configuration and scripts", &[]),
+                CommitType::new("chore")
+                    .with("Other changes that don't modify source or test files", &[]),
+                CommitType::new("docs").with("Documentation only changes", &["documentation"]),
+                CommitType::new("feat").with("A new feature", &["feature"]),
+                CommitType::new("fix").with("A bug fix", &["bug", "bugfix"]),
+                CommitType::new("perf")
+                    .with("A code change that improves performance", &["performance"]),
+                CommitType::new("refactor").with(
+                    "A code change that neither fixes a bug nor adds a feature",
+                    &["cleanup"],
+                ),
+                CommitType::new("revert").with("Reverts a previous commit", &["undo"]),
+                CommitType::new("style").with(
+                    "Formatting, white-space, etc; no change to what the code does",
+                    &["format"],
+                ),
+                CommitType::new("test").with(
+                    "Adding missing tests or correcting existing ones",
+                    &["tests"],
+                ),
+                CommitType::new("enhance").with(
+                    "Extends an existing feature, rather than adding a new one",
+                    &[],
+                ),
+                CommitType::new("improvement").with(
+                    "Improves existing behavior, e.g. usability, without a new feature",
+                    &[],
+                ),
+            ],
+            type_auto_submit: true,
+            custom_types: true,
+            review: true,
+            scopes: vec![],
+            scope_paths: vec![],
+            header_max_length: 100,
+            body_max_line_length: 72,
+            figlet_file: None,
+            changelog: ChangelogConfig::default(),
+        }
+    }
+}
+
+impl Default for ChangelogConfig {
+    fn default() -> Self {
+        ChangelogConfig {
+            sections: vec![
+                ("feat", "Features"),
+                ("fix", "Bug Fixes"),
+                ("perf", "Performance Improvements"),
+                ("enhance", "Enhancements"),
+                ("improvement", "Improvements"),
+                ("revert", "Reverts"),
+                ("refactor", "Code Refactoring"),
+                ("docs", "Documentation"),
+                ("build", "Build System"),
+                ("ci", "Continuous Integration"),
+                ("test", "Tests"),
+                ("style", "Styles"),
+                ("chore", "Chores"),
             ]
             .into_iter()
-            .map(String::from)
+            .map(|(ty, title)| ChangelogSection {
+                ty: ty.into(),
+                title: title.into(),
+            })
             .collect(),
-            figlet_file: None,
+            hidden: vec!["build", "ci", "chore", "style", "test"]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            commit_url: None,
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, err) => {
+                write!(f, "Failed to read config file {}: {}", path.display(), err)
+            }
+            ConfigError::Parse(path, message) => {
+                write!(
+                    f,
+                    "Failed to parse config file {}: {}",
+                    path.display(),
+                    message
+                )
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{Config, ConfigFile};
+    use crossterm::style::Color;
+    use std::path::Path;
+
+    #[test]
+    fn merge_overrides_only_given_fields() {
+        let mut config = Config::default();
+        let file: ConfigFile = toml::from_str(r#"types = ["feat", "fix"]"#).unwrap();
+        config.merge(file, None);
+
+        assert_eq!(config.type_names().collect::<Vec<_>>(), vec!["feat", "fix"]);
+        assert_eq!(config.figlet_file, None);
+
+        let file: ConfigFile = toml::from_str(r#"figlet_file = "fonts/big.flf""#).unwrap();
+        config.merge(file, Some(Path::new("/repo")));
+
+        assert_eq!(config.type_names().collect::<Vec<_>>(), vec!["feat", "fix"]);
+        assert_eq!(config.figlet_file, Some("/repo/fonts/big.flf".into()));
+    }
+
+    #[test]
+    fn rich_types() {
+        let mut config = Config::default();
+        let file: ConfigFile = toml::from_str(
+            r#"types = [
+                "fix",
+                { name = "wip", description = "Work in progress", emoji = "🚧", color = "yellow" },
+            ]"#,
+        )
+        .unwrap();
+        config.merge(file, None);
+
+        // "fix" keeps its default description and aliases
+        assert_eq!(
+            config.find_type("bug").map(|ty| ty.name.as_str()),
+            Some("fix")
+        );
+        assert!(config.types[0].description.is_some());
+        assert_eq!(config.types[1].emoji.as_deref(), Some("🚧"));
+        assert_eq!(config.types[1].color, Some(Color::Yellow));
+
+        let err = toml::from_str::<ConfigFile>(r#"types = [{ name = "x", color = "mauve" }]"#)
+            .unwrap_err();
+        assert!(err.to_string().contains("mauve"));
+    }
+
+    #[test]
+    fn custom_types() {
+        let mut config = Config::default();
+        assert_eq!(config.check_custom_type("wip"), Ok(()));
+        assert!(config.check_custom_type("w:ip").is_err());
+        assert!(config.check_custom_type("other-type").is_err());
+        assert!(config.check_custom_type("v2").is_err());
+        assert!(config.check_custom_type("wip!").is_err());
+
+        let file: ConfigFile = toml::from_str("custom_types = false").unwrap();
+        config.merge(file, None);
+        assert_eq!(config.check_custom_type("fix"), Ok(()));
+        assert!(config.check_custom_type("wip").is_err());
+    }
+
+    #[test]
+    fn parse_error_has_line() {
+        let err = toml::from_str::<ConfigFile>("types = [\"feat\"]\nfiglet_file =\n").unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+    }
+}
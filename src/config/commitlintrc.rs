@@ -0,0 +1,146 @@
+//! Reads the static commitlint config formats and maps the rules glint understands
+//! onto a config layer. `commitlint.config.js` can't be evaluated, so only
+//! `.commitlintrc`, `.commitlintrc.json`, `.commitlintrc.yaml`, `.commitlintrc.yml`,
+//! and the `commitlint` key of `package.json` are considered.
+
+use super::{CommitType, ConfigError, ConfigFile};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const RC_FILES: &[&str] = &[
+    ".commitlintrc",
+    ".commitlintrc.json",
+    ".commitlintrc.yaml",
+    ".commitlintrc.yml",
+    "package.json",
+];
+
+/// Returns the layer from the first commitlint config found in `repo_root`, along with
+/// the path it was read from.
+pub(super) fn load(repo_root: &Path) -> Result<Option<(PathBuf, ConfigFile)>, ConfigError> {
+    for name in RC_FILES {
+        let path = repo_root.join(name);
+        if !path.is_file() {
+            continue;
+        }
+
+        let text = fs::read_to_string(&path).map_err(|err| ConfigError::Io(path.clone(), err))?;
+        let value = parse(&path, &text)?;
+
+        let value = if *name == "package.json" {
+            match value.get("commitlint") {
+                Some(value) => value.clone(),
+                None => continue,
+            }
+        } else {
+            value
+        };
+
+        return Ok(Some((path, from_value(&value))));
+    }
+
+    Ok(None)
+}
+
+fn parse(path: &Path, text: &str) -> Result<Value, ConfigError> {
+    let is_json = path
+        .extension()
+        .map(|ext| ext == "json")
+        .unwrap_or_else(|| text.trim_start().starts_with('{'));
+
+    if is_json {
+        serde_json::from_str(text).map_err(|err| ConfigError::Parse(path.into(), err.to_string()))
+    } else {
+        serde_yaml::from_str(text).map_err(|err| ConfigError::Parse(path.into(), err.to_string()))
+    }
+}
+
+/// Maps `type-enum`, `scope-enum`, `header-max-length`, and `body-max-line-length` onto a
+/// config layer. Rules that are disabled (level 0) or use `never` are skipped, since glint
+/// can't express them.
+fn from_value(value: &Value) -> ConfigFile {
+    let rules = value.get("rules");
+    let rule = |name: &str| rules.and_then(|rules| active_rule_value(rules.get(name)?));
+
+    ConfigFile {
+        types: rule("type-enum")
+            .and_then(string_list)
+            .map(|names| names.into_iter().map(CommitType::new).collect()),
+        scopes: rule("scope-enum").and_then(string_list),
+        header_max_length: rule("header-max-length")
+            .and_then(Value::as_u64)
+            .map(|n| n as usize),
+        body_max_line_length: rule("body-max-line-length")
+            .and_then(Value::as_u64)
+            .map(|n| n as usize),
+        ..Default::default()
+    }
+}
+
+/// A commitlint rule is `[level, applicable, value]`.
+fn active_rule_value(rule: &Value) -> Option<&Value> {
+    let rule = rule.as_array()?;
+    let level = rule.first()?.as_u64()?;
+    let applicable = rule.get(1).and_then(Value::as_str).unwrap_or("always");
+
+    if level == 0 || applicable != "always" {
+        return None;
+    }
+
+    rule.get(2)
+}
+
+fn string_list(value: &Value) -> Option<Vec<String>> {
+    value
+        .as_array()?
+        .iter()
+        .map(|item| item.as_str().map(String::from))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{from_value, parse, CommitType};
+    use std::path::Path;
+
+    #[test]
+    fn maps_rules() {
+        let value = parse(
+            Path::new(".commitlintrc.yaml"),
+            r#"
+extends: ["@commitlint/config-conventional"]
+rules:
+  type-enum: [2, always, [feat, fix, chore]]
+  scope-enum: [0, always, [api]]
+  header-max-length: [2, always, 72]
+  body-max-line-length: [1, always, 80]
+"#,
+        )
+        .unwrap();
+        let file = from_value(&value);
+
+        assert_eq!(
+            file.types,
+            Some(vec![
+                CommitType::new("feat"),
+                CommitType::new("fix"),
+                CommitType::new("chore")
+            ])
+        );
+        assert_eq!(file.scopes, None);
+        assert_eq!(file.header_max_length, Some(72));
+        assert_eq!(file.body_max_line_length, Some(80));
+    }
+
+    #[test]
+    fn ignores_never_rules() {
+        let value = parse(
+            Path::new(".commitlintrc"),
+            r#"{ "rules": { "type-enum": [2, "never", ["wip"]] } }"#,
+        )
+        .unwrap();
+
+        assert_eq!(from_value(&value).types, None);
+    }
+}
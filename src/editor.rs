@@ -0,0 +1,74 @@
+//! Writing the commit body in the user's editor, for the body prompt.
+
+use crate::lint::strip_comments;
+use crate::{Commit, Git};
+use std::fs;
+use std::io;
+
+/// The text to edit: the body so far, then comments describing the commit.
+pub fn template<'a>(commit: &Commit, files: impl IntoIterator<Item = &'a str>) -> String {
+    let mut text = commit.body.clone().unwrap_or_default();
+    text.push_str("\n\n");
+    text.push_str("# Write the body of the commit message. Lines starting with '#' are\n");
+    text.push_str("# removed, and the rest is loaded back into glint to review.\n");
+    text.push_str("#\n");
+    text.push_str(&format!("# {}{}\n", commit.prefix(), commit.subject));
+
+    let mut files = files.into_iter().peekable();
+    if files.peek().is_some() {
+        text.push_str("#\n# Files to be committed:\n");
+        for file in files {
+            text.push_str(&format!("#\t{}\n", file));
+        }
+    }
+
+    text
+}
+
+/// Opens the body of `commit` in the editor git would use, and returns the edited body.
+pub fn edit_body<'a>(
+    git: &Git,
+    commit: &Commit,
+    files: impl IntoIterator<Item = &'a str>,
+) -> io::Result<String> {
+    let path = git.git_path("GLINT_EDITMSG")?;
+    fs::write(&path, template(commit, files))?;
+
+    git.edit(&git.editor()?, &path)?;
+
+    let text = fs::read_to_string(&path)?;
+    let _r = fs::remove_file(&path);
+    Ok(strip_comments(&text))
+}
+
+#[cfg(test)]
+mod test {
+    use super::template;
+    use crate::lint::strip_comments;
+    use crate::Commit;
+
+    #[test]
+    fn round_trip() {
+        let commit = Commit {
+            ty: "feat".into(),
+            scope: Some("api".into()),
+            subject: "add things".into(),
+            body: Some("First.\n\nSecond.".into()),
+            breaking: false,
+            footers: vec![],
+        };
+
+        let text = template(&commit, vec!["src/api.rs"]);
+        assert!(text.contains("# feat(api): add things\n"));
+        assert!(text.contains("#\tsrc/api.rs\n"));
+        assert_eq!(strip_comments(&text), "First.\n\nSecond.");
+
+        assert_eq!(strip_comments("\n# only comments\n  \n"), "");
+        assert_eq!(
+            strip_comments(
+                "    indented\n# ------------------------ >8 ------------------------\ndiff"
+            ),
+            "    indented"
+        );
+    }
+}
@@ -0,0 +1,149 @@
+//! Matching commit types and scopes against patterns like `feat`, `api/*`, or `!chore`.
+
+use crate::LogItem;
+
+/// A set of patterns. Values must match one of the plain patterns (if there are any) and
+/// none of the `!` negated ones. Patterns may be comma-separated, and support `*` and `?`.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Filter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl Filter {
+    pub fn new<S: AsRef<str>>(patterns: impl IntoIterator<Item = S>) -> Self {
+        let mut filter = Filter::default();
+
+        for patterns in patterns {
+            for pattern in patterns.as_ref().split(',').map(str::trim) {
+                match pattern.strip_prefix('!') {
+                    Some(negated) => filter.exclude.push(negated.to_string()),
+                    None if pattern.is_empty() => {}
+                    None => filter.include.push(pattern.to_string()),
+                }
+            }
+        }
+
+        filter
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    /// Checks `values`, e.g. each scope of a `feat(api,client): ...` commit. An empty list
+    /// only passes a filter without plain patterns.
+    pub fn matches(&self, values: &[&str]) -> bool {
+        let any_match = |patterns: &[String]| {
+            patterns
+                .iter()
+                .any(|pattern| values.iter().any(|value| glob_match(pattern, value)))
+        };
+
+        (self.include.is_empty() || any_match(&self.include)) && !any_match(&self.exclude)
+    }
+}
+
+/// Type and scope filters for `glint log`.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct LogFilter {
+    pub ty: Filter,
+    pub scope: Filter,
+}
+
+impl LogFilter {
+    pub fn is_empty(&self) -> bool {
+        self.ty.is_empty() && self.scope.is_empty()
+    }
+
+    /// Commits that aren't conventional have no type or scope.
+    pub fn matches(&self, item: &LogItem) -> bool {
+        let conv = item.as_conventional();
+        let ty: Vec<&str> = conv.iter().map(|conv| conv.ty).collect();
+        let scopes: Vec<&str> = conv
+            .iter()
+            .filter_map(|conv| conv.scope)
+            .flat_map(|scope| scope.split(','))
+            .map(str::trim)
+            .collect();
+
+        self.ty.matches(&ty) && self.scope.matches(&scopes)
+    }
+}
+
+/// Matches `text` against `pattern`, where `*` matches any run of characters (including `/`)
+/// and `?` matches a single character.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // Where to resume after the last `*`, if the current attempt fails
+    let mut backtrack = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, star_t)) => {
+                    p = star + 1;
+                    t = star_t + 1;
+                    backtrack = Some((star, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod test {
+    use super::{glob_match, Filter, LogFilter};
+    use crate::LogItem;
+
+    #[test]
+    fn globs() {
+        assert!(glob_match("api/*", "api/users"));
+        assert!(glob_match("api/*", "api/"));
+        assert!(!glob_match("api/*", "api"));
+        assert!(glob_match("*-ui", "admin-ui"));
+        assert!(glob_match("a*b*c", "aXXbYYbc"));
+        assert!(glob_match("fi?", "fix"));
+        assert!(!glob_match("fix", "fixup"));
+    }
+
+    #[test]
+    fn negation() {
+        let filter = Filter::new(["!chore", "!ci"]);
+        assert!(filter.matches(&["feat"]));
+        assert!(!filter.matches(&["chore"]));
+        assert!(filter.matches(&[]));
+
+        let filter = Filter::new(["feat,fix", "!fix"]);
+        assert!(filter.matches(&["feat"]));
+        assert!(!filter.matches(&["fix"]));
+        assert!(!filter.matches(&[]));
+    }
+
+    #[test]
+    fn multiple_scopes() {
+        let filter = LogFilter {
+            ty: Filter::new(["feat"]),
+            scope: Filter::new(["api/*"]),
+        };
+
+        assert!(filter.matches(&LogItem::test("feat(client, api/users): add")));
+        assert!(!filter.matches(&LogItem::test("feat(client): add")));
+        assert!(!filter.matches(&LogItem::test("fix(api/users): add")));
+        assert!(!filter.matches(&LogItem::test("not conventional")));
+    }
+}
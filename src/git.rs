@@ -1,13 +1,17 @@
+use crate::version::{self, Version};
 use std::env::current_dir;
 use std::ffi::OsStr;
 use std::fmt;
-use std::io::{self, BufRead, BufReader};
-use std::path::PathBuf;
-use std::process::{Command, Stdio};
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::thread::{self, JoinHandle};
 
+mod conventional;
 mod parse_log;
 
-pub use parse_log::LogItem;
+pub use conventional::{Conventional, Trailer};
+pub use parse_log::{parse_logs, FileChange, LogItem, ParseLogs, Signature};
 
 #[derive(Debug, Clone)]
 pub struct Git {
@@ -15,6 +19,66 @@ pub struct Git {
     repo_root: PathBuf,
 }
 
+/// Commits from a running `git log`, see `Git::log_parsed`. Dropping it early stops git.
+pub struct LogStream {
+    child: Child,
+    items: ParseLogs<BufReader<ChildStdout>>,
+    /// Reads git's error output as it's written, so git never blocks on a full pipe.
+    stderr: Option<JoinHandle<String>>,
+    waited: bool,
+}
+
+impl LogStream {
+    fn wait(&mut self) -> io::Result<()> {
+        self.waited = true;
+
+        let status = self.child.wait()?;
+        let stderr = self
+            .stderr
+            .take()
+            .and_then(|reader| reader.join().ok())
+            .unwrap_or_default();
+
+        if status.success() {
+            Ok(())
+        } else if stderr.trim().is_empty() {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("git log failed with {}", status),
+            ))
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                stderr.trim().to_string(),
+            ))
+        }
+    }
+}
+
+impl Iterator for LogStream {
+    type Item = io::Result<LogItem>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.waited {
+            return None;
+        }
+
+        match self.items.next() {
+            Some(item) => Some(item),
+            None => self.wait().err().map(Err),
+        }
+    }
+}
+
+impl Drop for LogStream {
+    fn drop(&mut self) {
+        if !self.waited {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct GitStatus(pub Vec<GitStatusItem>);
 
@@ -61,6 +125,101 @@ impl Git {
         }
     }
 
+    /// The directory containing `.git`.
+    pub fn repo_root(&self) -> &Path {
+        &self.repo_root
+    }
+
+    /// The directory git runs hooks from, which respects `core.hooksPath`.
+    pub fn hooks_dir(&self) -> io::Result<PathBuf> {
+        self.git_path("hooks")
+    }
+
+    /// The path of `name` in the git directory, e.g. `.git/COMMIT_EDITMSG`.
+    pub fn git_path(&self, name: &str) -> io::Result<PathBuf> {
+        let path = self.output(&["rev-parse", "--git-path", name])?;
+        Ok(self.repo_root.join(path.trim()))
+    }
+
+    /// The editor git would use for a commit message: `$GIT_EDITOR`, `core.editor`, `$VISUAL`,
+    /// `$EDITOR`, and then git's default.
+    pub fn editor(&self) -> io::Result<String> {
+        Ok(self.output(&["var", "GIT_EDITOR"])?.trim().to_string())
+    }
+
+    /// Opens `file` in `editor`, which is run by the shell like git does, so it may include
+    /// arguments. Fails if the editor exits with an error.
+    pub fn edit(&self, editor: &str, file: &Path) -> io::Result<()> {
+        let status = Command::new("sh")
+            .current_dir(&self.repo_root)
+            .arg("-c")
+            .arg(format!("{} \"$@\"", editor))
+            .arg(editor)
+            .arg(file)
+            .status()?;
+
+        if !status.success() {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("the editor '{}' exited with {}", editor, status),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Runs git with `args`, returning its output, or its error output as an error.
+    fn output(&self, args: &[&str]) -> io::Result<String> {
+        let output = Command::new("git")
+            .current_dir(&self.repo_root)
+            .stdin(Stdio::null())
+            .args(args)
+            .output()?;
+
+        if !output.status.success() {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            ));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// The most recent tag reachable from HEAD, if any. Only tags that are semantic versions,
+    /// like `v1.2.0`, count as releases.
+    pub fn latest_tag(&self) -> io::Result<Option<(String, Version)>> {
+        let tags = self.merged_tags()?;
+        let latest = version::latest_release(tags.iter().map(String::as_str));
+
+        Ok(latest.map(|(tag, version)| (tag.to_string(), version)))
+    }
+
+    /// All tags reachable from HEAD.
+    pub fn merged_tags(&self) -> io::Result<Vec<String>> {
+        let tags = self.output(&["tag", "--merged", "HEAD"])?;
+
+        Ok(tags.lines().map(String::from).collect())
+    }
+
+    /// Creates an annotated tag at HEAD.
+    pub fn tag(&self, name: &str, message: &str) -> Command {
+        let mut command = Command::new("git");
+
+        // Setup
+        command.current_dir(&self.cwd);
+        command.stdin(Stdio::null());
+
+        // Args
+        command.arg("tag");
+        command.arg("-a");
+        command.arg(name);
+        command.arg("-m");
+        command.arg(message);
+
+        command
+    }
+
     pub fn commit<I>(&self, message: &str, other_args: impl IntoIterator<Item = I>) -> Command
     where
         I: AsRef<OsStr>,
@@ -99,19 +258,36 @@ impl Git {
         }
         command.arg("--raw");
         command.arg("--pretty=raw");
+        command.arg("-z");
 
         command
     }
 
-    pub fn log_parsed<I>(&self, other_args: impl IntoIterator<Item = I>) -> io::Result<Vec<LogItem>>
+    /// Runs `git log`, parsing commits as they're printed. The iterator yields an error if
+    /// git fails, e.g. for an unknown revision.
+    pub fn log_parsed<I>(&self, other_args: impl IntoIterator<Item = I>) -> io::Result<LogStream>
     where
         I: AsRef<OsStr>,
     {
-        let proc = self.log(other_args).stdout(Stdio::piped()).spawn()?;
-        let stdout = proc.stdout.expect("must be able to access stdout");
-        Ok(parse_log::parse_logs(
-            BufReader::new(stdout).lines().filter_map(Result::ok),
-        ))
+        let mut child = self
+            .log(other_args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let stdout = child.stdout.take().expect("must be able to access stdout");
+        let mut stderr = child.stderr.take().expect("must be able to access stderr");
+        let stderr = thread::spawn(move || {
+            let mut output = String::new();
+            let _ = stderr.read_to_string(&mut output);
+            output
+        });
+
+        Ok(LogStream {
+            child,
+            items: parse_log::parse_logs(BufReader::new(stdout)),
+            stderr: Some(stderr),
+            waited: false,
+        })
     }
 
     /// Stages files using `git add`. Run from the repo root.gs
@@ -225,6 +401,13 @@ impl GitStatus {
         self.iter().any(|item| item.staged.is_some())
     }
 
+    /// Files with staged changes. Renames are shown as `old -> new`.
+    pub fn staged_files(&self) -> impl Iterator<Item = &str> {
+        self.iter()
+            .filter(|item| item.staged.is_some())
+            .map(GitStatusItem::file_name)
+    }
+
     pub fn any_unstaged(&self) -> bool {
         self.iter().any(|item| item.unstaged.is_some())
     }
@@ -0,0 +1,238 @@
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Conventional<'a> {
+    pub ty: &'a str,
+    pub scope: Option<&'a str>,
+    /// The rest of the first line, after the colon.
+    pub subject: &'a str,
+    /// Everything between the first line and the footers.
+    pub body: Option<&'a str>,
+    pub footers: Vec<Trailer<'a>>,
+    /// Set by a `!` before the colon, or a `BREAKING CHANGE` footer.
+    pub breaking: bool,
+    /// The description from a `BREAKING CHANGE` footer, if there is one.
+    pub breaking_change: Option<&'a str>,
+}
+
+/// A footer such as `Refs: #123`, `Reviewed-by: Name <email>`, or `BREAKING CHANGE: ...`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Trailer<'a> {
+    pub token: &'a str,
+    /// The value, including any indented continuation lines.
+    pub value: &'a str,
+}
+
+fn bytes_until_non_ws(s: &str) -> usize {
+    let mut offset = 0;
+    for c in s.chars() {
+        if c.is_whitespace() {
+            offset += c.len_utf8();
+        } else {
+            break;
+        }
+    }
+    offset
+}
+
+impl<'a> Trailer<'a> {
+    /// Parses `token: value` or `token #value`. Tokens are words joined by `-`, except for
+    /// the special `BREAKING CHANGE` token.
+    fn parse(line: &'a str) -> Option<Self> {
+        const BREAKING: &str = "BREAKING CHANGE";
+
+        let token_len = if line.starts_with(BREAKING) {
+            BREAKING.len()
+        } else {
+            line.find(|c: char| !(c.is_alphanumeric() || c == '-'))?
+        };
+
+        let token = &line[..token_len];
+        let rest = &line[token_len..];
+
+        let value = if rest.starts_with(": ") || rest == ":" || rest.starts_with(" #") {
+            &rest[1..]
+        } else {
+            return None;
+        };
+
+        if token.is_empty() {
+            return None;
+        }
+
+        Some(Trailer {
+            token,
+            value: value.trim_start_matches(' '),
+        })
+    }
+
+    pub fn is_breaking_change(&self) -> bool {
+        self.token == "BREAKING CHANGE" || self.token == "BREAKING-CHANGE"
+    }
+}
+
+/// Parses the trailers in `block` if every line is a trailer or an indented continuation
+/// of one, like `git interpret-trailers` does for the last paragraph of a message.
+fn parse_trailers(block: &str) -> Option<Vec<Trailer<'_>>> {
+    let mut trailers: Vec<Trailer<'_>> = vec![];
+
+    for line in block.lines() {
+        let is_continuation = line.starts_with(|c: char| c.is_whitespace());
+
+        match trailers.last_mut() {
+            Some(last) if is_continuation => {
+                // Extend the value's slice through the end of this line.
+                let start = last.value.as_ptr() as usize - block.as_ptr() as usize;
+                let end = line.as_ptr() as usize - block.as_ptr() as usize + line.len();
+                last.value = &block[start..end];
+            }
+            _ => trailers.push(Trailer::parse(line)?),
+        }
+    }
+
+    Some(trailers).filter(|trailers| !trailers.is_empty())
+}
+
+impl<'a> Conventional<'a> {
+    /// Parse a commit message into the components (type, scope, subject, body, footers).
+    /// Always returns slices of `message`.
+    pub fn parse(message: &'a str) -> Option<Self> {
+        let header_end = message.find('\n').unwrap_or(message.len());
+        let header = &message[..header_end];
+
+        let mut ty_pos = None;
+        let mut scope_pos = None;
+        let mut subject_pos = None;
+        let mut bang = false;
+
+        for (i, c) in header.char_indices() {
+            if ty_pos.is_none() {
+                if c == '(' || c == ':' || c == '!' {
+                    ty_pos = Some(0..i);
+
+                    if c == ':' {
+                        let start = i + 1 + bytes_until_non_ws(&header[i + 1..]);
+                        subject_pos = Some(start..header.len());
+                        break;
+                    }
+                } else if !c.is_alphabetic() {
+                    return None;
+                }
+            } else if c == ':' {
+                let start = i + 1 + bytes_until_non_ws(&header[i + 1..]);
+
+                bang = header[..i].ends_with('!');
+                subject_pos = Some(start..header.len());
+                break;
+            } else if c == ')' {
+                scope_pos = ty_pos.as_ref().map(|range| (range.end + 1)..i);
+            }
+
+            // The breaking change marker may only appear right before the colon.
+            if c == '!' && !header[i + 1..].starts_with(':') {
+                return None;
+            }
+        }
+
+        // The footers are the last paragraph, if it consists only of trailers. A message
+        // without a blank line after the header has no footers.
+        let rest = message[header_end..].trim_end();
+        let (body, footers) = match rest.rfind("\n\n") {
+            Some(i) => match parse_trailers(&rest[i + 2..]) {
+                Some(footers) => (&rest[..i], footers),
+                None => (rest, vec![]),
+            },
+            None => (rest, vec![]),
+        };
+        let body = Some(body.trim()).filter(|body| !body.is_empty());
+
+        let breaking_change = footers
+            .iter()
+            .find(|footer| footer.is_breaking_change())
+            .map(|footer| footer.value.trim());
+
+        match (ty_pos, scope_pos, subject_pos) {
+            (Some(ty), scope, Some(subject)) => Some(Conventional {
+                ty: &header[ty],
+                scope: scope.map(|scope| &header[scope]),
+                subject: header[subject].trim_end(),
+                body,
+                footers,
+                breaking: bang || breaking_change.is_some(),
+                breaking_change,
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Conventional, Trailer};
+
+    #[test]
+    fn breaking() {
+        let conv = Conventional::parse("feat(api)!: drop v1").unwrap();
+        assert_eq!(
+            (conv.ty, conv.scope, conv.subject),
+            ("feat", Some("api"), "drop v1")
+        );
+        assert!(conv.breaking);
+
+        let conv = Conventional::parse("feat!: drop v1").unwrap();
+        assert_eq!((conv.ty, conv.scope), ("feat", None));
+        assert!(conv.breaking);
+
+        let conv = Conventional::parse("fix: a\n\nBREAKING CHANGE: b is gone").unwrap();
+        assert!(conv.breaking);
+        assert_eq!(conv.breaking_change, Some("b is gone"));
+
+        assert_eq!(Conventional::parse("feat!(api): drop v1"), None);
+    }
+
+    #[test]
+    fn body_and_footers() {
+        let message = "fix(parser): handle tabs\n\nFirst paragraph.\n\nSecond: not a trailer because\nthis line isn't one.\n\nRefs: #123\nReviewed-by: Someone <a@b.c>\nBREAKING CHANGE: tabs are now\n  significant\nFixes #45";
+        let conv = Conventional::parse(message).unwrap();
+
+        assert_eq!(conv.subject, "handle tabs");
+        assert_eq!(
+            conv.body,
+            Some("First paragraph.\n\nSecond: not a trailer because\nthis line isn't one.")
+        );
+        assert_eq!(
+            conv.footers,
+            vec![
+                Trailer {
+                    token: "Refs",
+                    value: "#123"
+                },
+                Trailer {
+                    token: "Reviewed-by",
+                    value: "Someone <a@b.c>"
+                },
+                Trailer {
+                    token: "BREAKING CHANGE",
+                    value: "tabs are now\n  significant"
+                },
+                Trailer {
+                    token: "Fixes",
+                    value: "#45"
+                },
+            ]
+        );
+        assert!(conv.breaking);
+    }
+
+    #[test]
+    fn footer_only_message() {
+        let conv = Conventional::parse("docs: a\n\nSee: the docs for more").unwrap();
+        assert_eq!(conv.body, None);
+        assert_eq!(conv.footers.len(), 1);
+
+        let conv = Conventional::parse("docs: a\n\nSome text\nRefs: #1").unwrap();
+        assert_eq!(conv.body, Some("Some text\nRefs: #1"));
+        assert!(conv.footers.is_empty());
+
+        let conv = Conventional::parse("docs: a").unwrap();
+        assert_eq!((conv.body, conv.footers.len()), (None, 0));
+    }
+}
@@ -1,70 +1,191 @@
-#[derive(Debug, Clone, Eq, PartialEq)]
+use super::Conventional;
+use std::collections::VecDeque;
+use std::io::{self, BufRead};
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
 pub struct LogItem {
     pub commit: String,
-    pub epoch_secs: i64,
+    pub tree: String,
+    /// Empty for a root commit, and more than one for a merge.
+    pub parents: Vec<String>,
+    pub author: Signature,
+    pub committer: Signature,
     pub message: String,
-    pub files: Vec<String>,
+    pub files: Vec<FileChange>,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
-pub struct Conventional<'a> {
-    pub ty: &'a str,
-    pub scope: Option<&'a str>,
-    pub message: &'a str,
+/// A file changed by a commit, from a `--raw` line like
+/// `:100644 100644 6bbe237 4fe5fc6 R087\told/path\tnew/path`.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct FileChange {
+    pub old_mode: String,
+    pub new_mode: String,
+    /// Abbreviated blob hashes, all zeros for the missing side of an add or delete.
+    pub old_blob: String,
+    pub new_blob: String,
+    /// `A`dded, `M`odified, `D`eleted, `R`enamed, `C`opied, `T`ype changed, etc.
+    pub status: char,
+    /// How similar the old and new files are as a percentage, for renames and copies.
+    pub score: Option<u8>,
+    /// The source of a rename or copy.
+    pub old_path: Option<String>,
+    pub path: String,
 }
 
-fn bytes_until_non_ws(s: &str) -> usize {
-    let mut offset = 0;
-    for c in s.chars() {
-        if c.is_whitespace() {
-            offset += c.len_utf8();
-        } else {
-            break;
+/// The name, email, and time from an `author` or `committer` line.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Signature {
+    pub name: String,
+    pub email: String,
+    pub epoch_secs: i64,
+    /// The timezone the time was recorded in, e.g. 330 for `+0530`.
+    pub offset_mins: i32,
+}
+
+/// Builds log items for tests in other modules, e.g. `LogItem::test("feat: a").author("Ann", 0)`.
+#[cfg(test)]
+impl LogItem {
+    pub(crate) fn test(message: &str) -> Self {
+        LogItem {
+            message: message.into(),
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn commit(self, commit: &str) -> Self {
+        LogItem {
+            commit: commit.into(),
+            ..self
+        }
+    }
+
+    /// Sets both the author and the committer.
+    pub(crate) fn author(self, name: &str, epoch_secs: i64) -> Self {
+        let signature = Signature {
+            name: name.into(),
+            epoch_secs,
+            ..Default::default()
+        };
+
+        LogItem {
+            author: signature.clone(),
+            committer: signature,
+            ..self
         }
     }
-    offset
+
+    pub(crate) fn files(self, paths: &[&str]) -> Self {
+        let files = paths
+            .iter()
+            .map(|path| FileChange {
+                path: path.to_string(),
+                ..Default::default()
+            })
+            .collect();
+
+        LogItem { files, ..self }
+    }
 }
 
 impl LogItem {
-    /// Parse the message into the components (type, scope, message). Always returns
-    /// slices of the original message.
+    /// Parse the message into the components (type, scope, subject, body, footers). Always
+    /// returns slices of the original message.
     pub fn as_conventional(&self) -> Option<Conventional> {
-        let mut ty_pos = None;
-        let mut scope_pos = None;
-        let mut message_pos = None;
-
-        for (i, c) in self.message.char_indices() {
-            if ty_pos.is_none() {
-                if c == '(' || c == ':' {
-                    ty_pos = Some(0..i);
-
-                    if c == ':' {
-                        let start = i + 1 + bytes_until_non_ws(&self.message[i + 1..]);
-                        message_pos = Some(start..self.message.len());
-                        break;
-                    }
-                } else if !c.is_alphabetic() {
-                    return None;
-                }
-            } else if c == ':' {
-                let start = i + 1 + bytes_until_non_ws(&self.message[i + 1..]);
+        Conventional::parse(&self.message)
+    }
+
+    pub fn is_merge(&self) -> bool {
+        self.parents.len() > 1
+    }
+}
+
+impl Signature {
+    /// Parses `Name <email> 1568585467 -0700`.
+    fn parse(s: &str) -> Option<Self> {
+        let mut words = s.rsplitn(3, ' ');
+        let offset = words.next()?;
+        let epoch_secs = words.next()?.parse().ok()?;
+        let ident = words.next()?;
+
+        let (name, email) = match (ident.find('<'), ident.rfind('>')) {
+            (Some(open), Some(close)) if open < close => {
+                (ident[..open].trim(), &ident[open + 1..close])
+            }
+            _ => (ident.trim(), ""),
+        };
+
+        let sign = match offset.get(..1)? {
+            "+" => 1,
+            "-" => -1,
+            _ => return None,
+        };
+        let hhmm: i32 = offset.get(1..)?.parse().ok()?;
+
+        Some(Signature {
+            name: name.to_string(),
+            email: email.to_string(),
+            epoch_secs,
+            offset_mins: sign * (hhmm / 100 * 60 + hhmm % 100),
+        })
+    }
 
-                message_pos = Some(start..self.message.len());
-                break;
-            } else if c == ')' {
-                scope_pos = ty_pos.as_ref().map(|range| (range.end + 1)..i);
+    /// The time in this signature's timezone, in seconds since the unix epoch. Useful for
+    /// formatting the date the way the author saw it.
+    pub fn local_epoch_secs(&self) -> i64 {
+        self.epoch_secs + i64::from(self.offset_mins) * 60
+    }
+}
+
+impl FileChange {
+    /// Parses a `--raw` line. With `-z` the paths aren't on the line, and are filled in by
+    /// `add_path` instead.
+    fn parse(line: &str) -> Option<Self> {
+        let mut rest = line.strip_prefix(':')?;
+
+        let mut fields = [""; 5];
+        for field in fields.iter_mut() {
+            rest = rest.trim_start();
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            *field = &rest[..end];
+            rest = &rest[end..];
+        }
+        let [old_mode, new_mode, old_blob, new_blob, status] = fields;
+
+        let mut change = FileChange {
+            old_mode: old_mode.to_string(),
+            new_mode: new_mode.to_string(),
+            old_blob: old_blob.to_string(),
+            new_blob: new_blob.to_string(),
+            status: status.chars().next()?,
+            score: status.get(1..).and_then(|score| score.parse().ok()),
+            old_path: None,
+            path: String::new(),
+        };
+
+        let rest = rest.trim_start();
+        if !rest.is_empty() {
+            for path in rest.split('\t') {
+                change.add_path(path.to_string());
             }
         }
 
-        // println!("ty_pos: {:#?}\nscope: {:#?}\nmessage: {:#?}", ty_pos, scope_pos, message_pos);
+        Some(change)
+    }
 
-        match (ty_pos, scope_pos, message_pos) {
-            (Some(ty), scope, Some(message)) => Some(Conventional {
-                ty: &self.message[ty],
-                scope: scope.map(|scope| &self.message[scope]),
-                message: &self.message[message],
-            }),
-            _ => None,
+    fn has_old_path(&self) -> bool {
+        self.status == 'R' || self.status == 'C'
+    }
+
+    fn is_complete(&self) -> bool {
+        !self.path.is_empty()
+    }
+
+    /// Renames and copies have two paths, the source first.
+    fn add_path(&mut self, path: String) {
+        if self.has_old_path() && self.old_path.is_none() {
+            self.old_path = Some(path);
+        } else {
+            self.path = path;
         }
     }
 }
@@ -72,32 +193,67 @@ impl LogItem {
 /// Parses `git log --format=raw --raw`
 enum Parser {
     SeekingHeader,
-    Header {
-        commit: String,
-    },
-    Header2 {
-        commit: String,
-        epoch_secs: i64,
-    },
-    PreMessage {
-        commit: String,
-        epoch_secs: i64,
-    },
-    MsgFooter {
-        commit: String,
-        epoch_secs: i64,
-        message: String,
-        files: Vec<String>,
-    },
+    /// Reading `tree`, `parent`, and `author` lines, up to `committer`.
+    Header(LogItem),
+    /// Past the committer, waiting for the blank line before the message.
+    Header2(LogItem),
+    PreMessage(LogItem),
+    MsgFooter(LogItem),
     Complete(Option<LogItem>),
     Void,
 }
 
+/// Strips the four spaces git indents message lines with. Blank lines in the message are
+/// just the indentation.
+fn unindent(line: &str) -> &str {
+    let offset = line.char_indices().nth(4).map_or(line.len(), |(i, _)| i);
+    &line[offset..]
+}
+
+fn complete(mut item: LogItem) -> LogItem {
+    item.message = item.message.trim_end().into();
+    item
+}
+
 impl Parser {
     fn take(&mut self) -> Self {
         std::mem::replace(self, Parser::Void)
     }
 
+    /// Completes an item whose end wasn't marked by a blank line, which happens for the
+    /// last commit in the output, and for commits without a file list (e.g. merges).
+    fn finish(&mut self) -> Option<LogItem> {
+        match self.take() {
+            Parser::MsgFooter(item) => {
+                *self = Parser::SeekingHeader;
+                Some(complete(item))
+            }
+            state => {
+                *self = state;
+                None
+            }
+        }
+    }
+
+    /// With `-z`, whether the next NUL-terminated field is a path for the last file rather
+    /// than more lines.
+    fn expects_path(&self) -> bool {
+        match self {
+            Parser::MsgFooter(item) => {
+                matches!(item.files.last(), Some(file) if !file.is_complete())
+            }
+            _ => false,
+        }
+    }
+
+    fn handle_path(&mut self, path: String) {
+        if let Parser::MsgFooter(item) = self {
+            if let Some(file) = item.files.last_mut() {
+                file.add_path(path);
+            }
+        }
+    }
+
     fn handle_line(&mut self, line: String) {
         use Parser::*;
 
@@ -109,111 +265,72 @@ impl Parser {
 
         let state = self.take();
         *self = match state {
-            SeekingHeader if line.starts_with("commit ") => Header {
+            SeekingHeader if line.starts_with("commit ") => Header(LogItem {
                 commit: line["commit ".len()..].to_string(),
-            },
-            Header { .. } if liner.starts_with("committer ") => {
-                let mut time_word = None;
-                for word in liner.split_whitespace().rev() {
-                    if !word.is_empty()
-                        && word.chars().next().filter(|c| c.is_ascii_digit()).is_some()
-                    {
-                        if let Ok(epoch_secs) = word.parse() {
-                            time_word = Some(epoch_secs);
-                            break;
-                        }
+                ..LogItem::default()
+            }),
+            Header(mut item) if liner.starts_with("committer ") => {
+                match Signature::parse(&liner["committer ".len()..]) {
+                    Some(committer) => {
+                        item.committer = committer;
+                        Header2(item)
                     }
+                    None => Header(item),
                 }
-
-                if let Header { commit } = state {
-                    match time_word {
-                        Some(epoch_secs) => Header2 { commit, epoch_secs },
-                        _ => Header { commit },
-                    }
-                } else {
-                    unreachable!()
+            }
+            Header(mut item) => {
+                if let Some(tree) = liner.strip_prefix("tree ") {
+                    item.tree = tree.to_string();
+                } else if let Some(parent) = liner.strip_prefix("parent ") {
+                    item.parents.push(parent.to_string());
+                } else if let Some(author) = liner.strip_prefix("author ") {
+                    item.author = Signature::parse(author).unwrap_or_default();
                 }
+
+                Header(item)
             }
-            Header2 { commit, epoch_secs } => {
+            Header2(item) => {
                 if is_blank {
-                    PreMessage { commit, epoch_secs }
+                    PreMessage(item)
                 } else {
-                    Header2 { commit, epoch_secs }
+                    Header2(item)
                 }
             }
-            PreMessage { commit, epoch_secs } => {
-                let message = if starts_four_spaces {
-                    let (i, _) = line.char_indices().nth(4).unwrap();
-                    liner[i..].to_string()
+            PreMessage(mut item) => {
+                item.message = if starts_four_spaces {
+                    unindent(liner).to_string()
                 } else {
                     line
                 };
 
-                MsgFooter {
-                    commit,
-                    epoch_secs,
-                    message,
-                    files: vec![],
-                }
+                MsgFooter(item)
             }
 
-            MsgFooter {
-                commit,
-                epoch_secs,
-                mut message,
-                mut files,
-            } => {
+            MsgFooter(mut item) => {
                 let msg_addition = if starts_four_spaces {
-                    line.char_indices().nth(4).map(|(i, _)| &liner[i..])
+                    Some(unindent(liner))
                 } else {
                     None
                 };
 
-                if liner.is_empty() && files.is_empty() {
-                    message.push('\n');
-                    MsgFooter {
-                        commit,
-                        epoch_secs,
-                        message,
-                        files,
-                    }
+                if liner.is_empty() && item.files.is_empty() {
+                    item.message.push('\n');
+                    MsgFooter(item)
                 } else if let Some(msg_addition) = msg_addition {
-                    if !message.is_empty() {
-                        message.push('\n');
+                    if !item.message.is_empty() {
+                        item.message.push('\n');
                     }
 
-                    message.push_str(msg_addition);
+                    item.message.push_str(msg_addition);
 
-                    MsgFooter {
-                        commit,
-                        epoch_secs,
-                        message,
-                        files,
-                    }
+                    MsgFooter(item)
                 } else if line.starts_with(':') {
-                    if let Some(name) = liner.split_whitespace().last() {
-                        files.push(name.to_string());
-                    }
-                    MsgFooter {
-                        commit,
-                        epoch_secs,
-                        message,
-                        files,
-                    }
+                    item.files.extend(FileChange::parse(liner));
+                    MsgFooter(item)
                 } else if liner.is_empty() {
-                    Complete(Some(LogItem {
-                        commit,
-                        epoch_secs,
-                        message: message.trim_end().into(),
-                        files,
-                    }))
+                    Complete(Some(complete(item)))
                 } else {
-                    MsgFooter {
-                        commit,
-                        epoch_secs,
-                        message,
-                        files,
-                    }
+                    MsgFooter(item)
                 }
             }
             x => x,
@@ -221,30 +338,85 @@ impl Parser {
     }
 }
 
-pub fn parse_logs(lines: impl Iterator<Item = String>) -> Vec<LogItem> {
-    let mut parser = Parser::SeekingHeader;
-    let mut items = vec![];
+/// Feeds `line` to the parser, collecting the item it completes, if any.
+fn feed(parser: &mut Parser, items: &mut VecDeque<LogItem>, line: String) {
+    if line.starts_with("commit ") {
+        items.extend(parser.finish());
+    }
+
+    parser.handle_line(line);
+
+    *parser = match parser.take() {
+        Parser::Complete(item) => {
+            items.extend(item);
+            Parser::SeekingHeader
+        }
+        state => state,
+    };
+}
+
+/// Lazily parses `git log --format=raw --raw -z`, yielding each commit once it's complete.
+/// Output without `-z` works too, as long as no paths needed quoting.
+pub struct ParseLogs<R> {
+    fields: io::Split<R>,
+    parser: Parser,
+    items: VecDeque<LogItem>,
+    done: bool,
+}
 
-    for line in lines {
-        parser.handle_line(line);
+pub fn parse_logs<R: BufRead>(reader: R) -> ParseLogs<R> {
+    ParseLogs {
+        fields: reader.split(b'\0'),
+        parser: Parser::SeekingHeader,
+        items: VecDeque::new(),
+        done: false,
+    }
+}
 
-        parser = match parser {
-            Parser::Complete(None) => Parser::SeekingHeader,
-            Parser::Complete(Some(item)) => {
-                items.push(item);
-                Parser::SeekingHeader
+impl<R: BufRead> ParseLogs<R> {
+    /// Each NUL-terminated field is either some newline-separated lines, or a path for the
+    /// preceding file.
+    fn handle_field(&mut self, field: String) {
+        if self.parser.expects_path() {
+            self.parser.handle_path(field);
+        } else {
+            for line in field.split('\n') {
+                feed(&mut self.parser, &mut self.items, line.to_string());
             }
-            _ => parser,
-        };
+        }
     }
+}
+
+impl<R: BufRead> Iterator for ParseLogs<R> {
+    type Item = io::Result<LogItem>;
 
-    items
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.items.pop_front() {
+                return Some(Ok(item));
+            }
+            if self.done {
+                return None;
+            }
+
+            match self.fields.next() {
+                Some(Ok(field)) => self.handle_field(String::from_utf8_lossy(&field).into_owned()),
+                Some(Err(err)) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+                None => {
+                    self.done = true;
+                    self.items.extend(self.parser.finish());
+                }
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use super::{parse_logs, Conventional, LogItem};
-    use std::io::{BufRead, BufReader};
+    use super::{parse_logs, Conventional, FileChange, LogItem, Signature};
 
     // Note: the whitespace here is important, and there is
     // one trailing blank line. The sanity check enforces this
@@ -262,6 +434,10 @@ committer Frankie Bagnardi <f.bagnardi@gmail.com> 1568585467 -0700
 
 "#;
 
+    fn parse(raw: &str) -> Vec<LogItem> {
+        parse_logs(raw.as_bytes()).map(Result::unwrap).collect()
+    }
+
     #[test]
     fn sanity() {
         assert_eq!(&RAW[0..6], "commit");
@@ -270,34 +446,126 @@ committer Frankie Bagnardi <f.bagnardi@gmail.com> 1568585467 -0700
 
     #[test]
     fn parse_initial() {
-        let lines = BufReader::new(RAW.as_bytes())
-            .lines()
-            .filter_map(|r| r.ok());
-        let logs = parse_logs(lines);
+        let logs = parse(RAW);
+        let frankie = Signature {
+            name: "Frankie Bagnardi".into(),
+            email: "f.bagnardi@gmail.com".into(),
+            epoch_secs: 1568585467,
+            offset_mins: -420,
+        };
         assert_eq!(logs.len(), 1);
         assert_eq!(
             logs[0],
             LogItem {
                 commit: "18d90e52cf8d6a486bee299b3949ebd213c85f2a".into(),
-                epoch_secs: 1568585467,
+                tree: "f221c23e63d1fe5b52d5acf39599fa02e2a69fc0".into(),
+                parents: vec!["089918cea42077b499ff092113ced60451214912".into()],
+                author: frankie.clone(),
+                committer: frankie,
                 message: "docs(gif): updates usage gif\n\nmuch better".into(),
-                files: vec!["assets/usage.gif".into()],
+                files: vec![FileChange {
+                    old_mode: "100644".into(),
+                    new_mode: "100644".into(),
+                    old_blob: "6bbe237".into(),
+                    new_blob: "4fe5fc6".into(),
+                    status: 'M',
+                    score: None,
+                    old_path: None,
+                    path: "assets/usage.gif".into(),
+                }],
             }
         );
     }
 
+    #[test]
+    fn parse_unterminated() {
+        let raw = format!(
+            "{}{}",
+            RAW.replace(
+                ":100644 100644 6bbe237 4fe5fc6 M        assets/usage.gif\n\n",
+                ""
+            ),
+            RAW.trim_end()
+        );
+        let logs = parse(&raw);
+        assert_eq!(logs.len(), 2);
+        assert_eq!(
+            logs[0].message,
+            "docs(gif): updates usage gif\n\nmuch better"
+        );
+        assert!(logs[0].files.is_empty());
+        assert_eq!(logs[1].files[0].path, "assets/usage.gif");
+    }
+
+    #[test]
+    fn parse_z() {
+        let header = RAW.split("\n\n").next().unwrap();
+        let raw = format!(
+            "{h}\n\n    first\n\n:000000 100644 0000000 4fe5fc6 A\0new\tfile\0\
+             :100644 100644 6bbe237 6bbe237 R087\0old name\0new name\0\0\
+             {h}\n\n    merge\n\0{h}\n\n    last\n\n:100644 000000 6bbe237 0000000 D\0gone\0",
+            h = header
+        );
+        let logs = parse(&raw);
+
+        assert_eq!(logs.len(), 3);
+        assert_eq!(logs[0].message, "first");
+        assert_eq!(
+            logs[0].files.iter().map(|f| f.status).collect::<Vec<_>>(),
+            vec!['A', 'R']
+        );
+        assert_eq!(logs[0].files[0].path, "new\tfile");
+        assert_eq!(
+            (
+                logs[0].files[1].old_path.as_deref(),
+                logs[0].files[1].path.as_str(),
+                logs[0].files[1].score
+            ),
+            (Some("old name"), "new name", Some(87))
+        );
+        assert!(logs[1].files.is_empty());
+        assert_eq!(
+            (logs[2].files[0].status, logs[2].files[0].path.as_str()),
+            ('D', "gone")
+        );
+    }
+
+    #[test]
+    fn parse_signature() {
+        let signature = Signature::parse("Jo <jo@example.com> 1568585467 +0530").unwrap();
+        assert_eq!(
+            (signature.name.as_str(), signature.offset_mins),
+            ("Jo", 330)
+        );
+        assert_eq!(signature.local_epoch_secs(), 1568585467 + 330 * 60);
+
+        assert_eq!(Signature::parse("Jo <jo@example.com> 1568585467"), None);
+    }
+
+    #[test]
+    fn parse_indented_blank_lines() {
+        // Newer versions of git indent the blank lines in the message too
+        let raw = RAW.replace("gif\n\n    much", "gif\n    \n    much");
+        let logs = parse(&raw);
+        assert_eq!(
+            logs[0].message,
+            "docs(gif): updates usage gif\n\nmuch better"
+        );
+    }
+
     #[test]
     fn as_conventional() {
-        let lines = BufReader::new(RAW.as_bytes())
-            .lines()
-            .filter_map(|r| r.ok());
-        let logs = parse_logs(lines);
+        let logs = parse(RAW);
         assert_eq!(
             logs[0].as_conventional(),
             Some(Conventional {
                 ty: "docs",
                 scope: Some("gif"),
-                message: "updates usage gif\n\nmuch better"
+                subject: "updates usage gif",
+                body: Some("much better"),
+                footers: vec![],
+                breaking: false,
+                breaking_change: None,
             })
         );
     }
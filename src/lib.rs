@@ -1,14 +1,23 @@
+pub mod changelog;
 mod color;
 mod commitlint;
 mod config;
+pub mod editor;
 pub mod figlet;
+pub mod filter;
 mod git;
+pub mod lint;
 pub mod prompt;
+pub mod scopes;
+pub mod stats;
 pub mod string;
 pub mod term_buffer;
+pub mod version;
 
-pub use commitlint::Commit;
-pub use config::Config;
+pub use commitlint::{Commit, Footer};
+pub use config::{ChangelogConfig, ChangelogSection, CommitType, Config, ConfigError};
 pub use figlet::Figlet;
-pub use git::Git;
+pub use git::{
+    parse_logs, Conventional, FileChange, Git, LogItem, LogStream, ParseLogs, Signature, Trailer,
+};
 pub use term_buffer::TermBuffer;
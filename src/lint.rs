@@ -0,0 +1,209 @@
+use crate::git::Conventional;
+use crate::string;
+use crate::Config;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// The commitlint name of the rule, e.g. "type-enum".
+    pub rule: &'static str,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn error(rule: &'static str, message: impl Into<String>) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            rule,
+            message: message.into(),
+        }
+    }
+
+    fn warning(rule: &'static str, message: impl Into<String>) -> Self {
+        Diagnostic {
+            severity: Severity::Warning,
+            rule,
+            message: message.into(),
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+/// Messages that git or other tools generate, which commitlint also skips by default.
+pub fn is_ignored(message: &str) -> bool {
+    ["Merge ", "Revert \"", "fixup! ", "squash! ", "amend! "]
+        .iter()
+        .any(|prefix| message.starts_with(prefix))
+}
+
+/// Removes the lines git would strip from a message file with the default cleanup mode:
+/// `#` comments, everything below the scissors line, and surrounding blank lines.
+pub fn strip_comments(message: &str) -> String {
+    let lines: Vec<&str> = message
+        .lines()
+        .take_while(|line| !line.starts_with("# ------------------------ >8"))
+        .filter(|line| !line.starts_with('#'))
+        .map(str::trim_end)
+        .collect();
+
+    lines.join("\n").trim_matches('\n').to_string()
+}
+
+/// Checks a commit message against the rules derived from `config`.
+pub fn lint(message: &str, config: &Config) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+
+    let mut lines = message.lines();
+    let header = lines.next().unwrap_or("");
+
+    let header_len = string::len(header);
+    if header_len > config.header_max_length {
+        diagnostics.push(Diagnostic::error(
+            "header-max-length",
+            format!(
+                "header must not be longer than {} characters, current length is {}",
+                config.header_max_length, header_len
+            ),
+        ));
+    }
+
+    match Conventional::parse(header) {
+        Some(conv) => lint_header(&conv, config, &mut diagnostics),
+        None => diagnostics.push(Diagnostic::error(
+            "header-format",
+            "header must be in the form 'type(scope): subject'",
+        )),
+    }
+
+    if lines
+        .next()
+        .filter(|line| !line.trim().is_empty())
+        .is_some()
+    {
+        diagnostics.push(Diagnostic::warning(
+            "body-leading-blank",
+            "body must have a leading blank line",
+        ));
+    }
+
+    diagnostics
+}
+
+fn lint_header(conv: &Conventional, config: &Config, diagnostics: &mut Vec<Diagnostic>) {
+    if conv.ty.is_empty() {
+        diagnostics.push(Diagnostic::error("type-empty", "type may not be empty"));
+    } else if !config.type_names().any(|ty| ty == conv.ty) {
+        diagnostics.push(Diagnostic::error(
+            "type-enum",
+            format!(
+                "type must be one of [{}]",
+                config.type_names().collect::<Vec<_>>().join(", ")
+            ),
+        ));
+    }
+
+    if let Some(scope) = conv.scope {
+        if scope.chars().any(char::is_uppercase) {
+            diagnostics.push(Diagnostic::error("scope-case", "scope must be lower-case"));
+        }
+
+        if !config.scopes.is_empty() {
+            let unknown = scope
+                .split(',')
+                .map(str::trim)
+                .any(|scope| !config.scopes.iter().any(|known| known == scope));
+
+            if unknown {
+                diagnostics.push(Diagnostic::error(
+                    "scope-enum",
+                    format!("scope must be one of [{}]", config.scopes.join(", ")),
+                ));
+            }
+        }
+    }
+
+    let subject = conv.subject;
+    if subject.is_empty() {
+        diagnostics.push(Diagnostic::error(
+            "subject-empty",
+            "subject may not be empty",
+        ));
+    } else if subject.ends_with('.') {
+        diagnostics.push(Diagnostic::error(
+            "subject-full-stop",
+            "subject may not end with a full stop",
+        ));
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "⚠"),
+            Severity::Error => write!(f, "✖"),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} [{}]", self.severity, self.message, self.rule)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{lint, strip_comments};
+    use crate::Config;
+
+    fn rules(message: &str) -> Vec<&'static str> {
+        lint(message, &Config::default())
+            .into_iter()
+            .map(|d| d.rule)
+            .collect()
+    }
+
+    #[test]
+    fn valid() {
+        assert!(rules("feat(client): add login").is_empty());
+        assert!(rules("fix: typo\n\nlonger explanation").is_empty());
+    }
+
+    #[test]
+    fn header_rules() {
+        assert_eq!(rules("added login"), vec!["header-format"]);
+        assert_eq!(rules(": add login"), vec!["type-empty"]);
+        assert_eq!(rules("(api): add login"), vec!["type-empty"]);
+        assert_eq!(rules("wip: login"), vec!["type-enum"]);
+        assert_eq!(
+            rules("feat(Client): add login."),
+            vec!["scope-case", "subject-full-stop"]
+        );
+        assert_eq!(rules("feat: "), vec!["subject-empty"]);
+        assert_eq!(
+            rules(&format!("feat: {}", "a".repeat(100))),
+            vec!["header-max-length"]
+        );
+    }
+
+    #[test]
+    fn body_leading_blank() {
+        assert_eq!(rules("feat: a\nb"), vec!["body-leading-blank"]);
+    }
+
+    #[test]
+    fn strips_comments() {
+        let message = "feat: a\n\nbody\n# Please enter the commit message\n# ------------------------ >8 ------------------------\ndiff";
+        assert_eq!(strip_comments(message), "feat: a\n\nbody");
+    }
+}
@@ -1,9 +1,18 @@
 mod files_prompt;
+mod footer_prompt;
+mod fuzzy;
+mod line_input;
 mod message_prompt;
+mod review_prompt;
 mod scope_prompt;
+mod subject_prompt;
+mod text_area;
 mod type_prompt;
 
 pub use files_prompt::{FilesPrompt, FilesPromptResult};
+pub use footer_prompt::{FooterPrompt, FooterPromptResult};
 pub use message_prompt::{MessagePrompt, MessagePromptResult};
+pub use review_prompt::{ReviewEdit, ReviewPrompt, ReviewPromptResult};
 pub use scope_prompt::{ScopePrompt, ScopePromptResult};
+pub use subject_prompt::{SubjectPrompt, SubjectPromptResult};
 pub use type_prompt::{TypePrompt, TypePromptResult};
@@ -1,8 +1,10 @@
 use crate::color::reset_display;
 use crate::git::{Git, GitStatus, GitStatusItem, GitStatusType};
+use crate::string;
 use crate::Config;
 use crate::TermBuffer;
 use crossterm::{
+    self as ct,
     event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
     style::{style, Color},
 };
@@ -34,6 +36,14 @@ impl<'a> FilesPrompt<'a> {
         }
     }
 
+    /// Starts with `files` checked, e.g. when coming back from the review.
+    pub fn prefill(mut self, files: &[String]) -> Self {
+        for (checked, item) in self.checked.iter_mut().zip(self.options.iter()) {
+            *checked = files.iter().any(|file| file == item.file_name());
+        }
+        self
+    }
+
     pub fn run(mut self) -> FilesPromptResult {
         let mut buffer = TermBuffer::new();
 
@@ -170,6 +180,10 @@ impl<'a> FilesPrompt<'a> {
             let focused_color = Color::Blue;
             let default_color = Color::Reset;
 
+            // Leave room for the checkbox and status before the name
+            let (term_width, _) = ct::terminal::size().unwrap_or((80, 24));
+            let name_width = (term_width as usize).saturating_sub(5);
+
             let status_untracked = style('+').with(Color::Rgb {
                 r: 96,
                 g: 218,
@@ -217,7 +231,9 @@ impl<'a> FilesPrompt<'a> {
                     _ => &status_none,
                 };
 
-                let file_name = style(git_status_item.file_name()).with(line_color);
+                let file_name =
+                    string::truncate_with_ellipsis(git_status_item.file_name(), name_width);
+                let file_name = style(file_name).with(line_color);
 
                 let line = format!(
                     "{} {} {}{}",
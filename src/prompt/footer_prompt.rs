@@ -0,0 +1,124 @@
+use super::line_input::LineInput;
+use crate::color::reset_display;
+use crate::string;
+use crate::Footer;
+use crate::TermBuffer;
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    style::{style, Color},
+};
+
+/// Prompts for footers like `Closes #12` or `Reviewed-by: Name`, one per line. Enter on an
+/// empty line finishes, and Backspace on an empty line removes the last footer.
+#[derive(Debug)]
+pub struct FooterPrompt {
+    footers: Vec<Footer>,
+    input: LineInput,
+    error: Option<String>,
+}
+
+pub enum FooterPromptResult {
+    Footers(Vec<Footer>),
+    Escape,
+    Terminate,
+}
+
+impl FooterPrompt {
+    /// Starts with `footers`, e.g. a `BREAKING CHANGE` from the command line.
+    pub fn new(footers: Vec<Footer>) -> Self {
+        FooterPrompt {
+            footers,
+            input: LineInput::default(),
+            error: None,
+        }
+    }
+
+    pub fn run(mut self) -> FooterPromptResult {
+        let mut buffer = TermBuffer::new();
+
+        let mut first_iteration = true;
+
+        loop {
+            let event = if first_iteration {
+                first_iteration = false;
+                None
+            } else {
+                match event::read() {
+                    Ok(Event::Key(KeyEvent { code, modifiers })) => Some((
+                        code,
+                        modifiers.contains(KeyModifiers::CONTROL),
+                        modifiers.contains(KeyModifiers::SHIFT),
+                        modifiers.contains(KeyModifiers::ALT),
+                    )),
+                    _ => continue,
+                }
+            };
+
+            match event {
+                Some((KeyCode::Char('c'), true, false, false)) => {
+                    return FooterPromptResult::Terminate;
+                }
+                Some((KeyCode::Enter, false, _, false)) if self.input.text.trim().is_empty() => {
+                    return FooterPromptResult::Footers(self.footers);
+                }
+                Some((KeyCode::Enter, false, _, false)) => match Footer::parse(&self.input.text) {
+                    Ok(footer) => {
+                        self.footers.push(footer);
+                        self.input = LineInput::default();
+                        self.error = None;
+                    }
+                    Err(err) => self.error = Some(err),
+                },
+                Some((KeyCode::Backspace, false, _, false)) if self.input.text.is_empty() => {
+                    if let Some(footer) = self.footers.pop() {
+                        self.input = LineInput::new(format!("{}: {}", footer.token, footer.value));
+                    }
+                }
+                Some((KeyCode::Esc, false, _, false)) => {
+                    return FooterPromptResult::Escape;
+                }
+                Some((code, ctrl, _, alt)) => {
+                    if !self.input.handle_key(code, ctrl, alt) {
+                        continue;
+                    }
+                    self.error = None;
+                }
+                None => {}
+            };
+
+            let instructions =
+                "Footers, e.g. \"Closes #12\" (optional, Enter on an empty line when done):";
+            buffer.push_line(instructions);
+            buffer.push_line("-".repeat(instructions.len()));
+
+            for footer in &self.footers {
+                buffer.push_line(format!(
+                    "{}{}{}",
+                    style(format!("{}: ", footer.token)).with(Color::Blue),
+                    footer.value,
+                    reset_display()
+                ));
+            }
+
+            let y = buffer.lines();
+            buffer.push_line(format!(
+                "{}{}",
+                style(&self.input.text).with(crate::color::theme_user_input()),
+                reset_display()
+            ));
+
+            if let Some(error) = &self.error {
+                buffer.push_line(format!(
+                    "{}{}",
+                    style(error).with(Color::Red),
+                    reset_display()
+                ));
+            }
+
+            let (before_cursor, _) = string::split_at(&self.input.text, self.input.cursor);
+            buffer.set_next_cursor((string::width(before_cursor) as u16, y));
+            buffer.render_frame();
+            buffer.flush();
+        }
+    }
+}
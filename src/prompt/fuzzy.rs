@@ -0,0 +1,78 @@
+//! Subsequence matching for filtering prompt options, e.g. "ft" matches "feat".
+
+/// Where a pattern matched, and how well.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FuzzyMatch {
+    pub score: i32,
+    /// The char indices in the text that matched the pattern.
+    pub indices: Vec<usize>,
+}
+
+/// Matches `pattern` as a case-insensitive subsequence of `text`. Matches at the start of
+/// the text or of a word, and runs of consecutive characters, score higher; gaps score lower.
+///
+/// Each pattern character takes the first possible match, except that a match right after
+/// the previous one, or at the start of a word, is preferred when one exists.
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<FuzzyMatch> {
+    let text: Vec<char> = text.chars().flat_map(char::to_lowercase).collect();
+    let mut indices = Vec::new();
+    let mut score = 0;
+    let mut start = 0;
+
+    for p in pattern.chars().flat_map(char::to_lowercase) {
+        let candidates: Vec<usize> = (start..text.len()).filter(|&i| text[i] == p).collect();
+        let is_word_start = |i: usize| i == 0 || matches!(text[i - 1], '-' | '_' | '/' | ' ');
+
+        let i = candidates
+            .iter()
+            .copied()
+            .find(|&i| i == start && start > 0)
+            .or_else(|| candidates.iter().copied().find(|&i| is_word_start(i)))
+            .or_else(|| candidates.first().copied())?;
+
+        score += 1;
+        if i == 0 {
+            score += 8;
+        } else if is_word_start(i) {
+            score += 4;
+        }
+        if i > 0 && indices.last() == Some(&(i - 1)) {
+            score += 5;
+        }
+        score -= (i - start) as i32;
+
+        indices.push(i);
+        start = i + 1;
+    }
+
+    Some(FuzzyMatch { score, indices })
+}
+
+#[cfg(test)]
+mod test {
+    use super::fuzzy_match;
+
+    fn score(pattern: &str, text: &str) -> Option<i32> {
+        fuzzy_match(pattern, text).map(|m| m.score)
+    }
+
+    #[test]
+    fn subsequences() {
+        assert_eq!(fuzzy_match("ft", "feat").unwrap().indices, vec![0, 3]);
+        assert_eq!(fuzzy_match("FIX", "fix").unwrap().indices, vec![0, 1, 2]);
+        assert_eq!(fuzzy_match("xf", "fix"), None);
+        assert_eq!(fuzzy_match("", "fix").unwrap().indices, Vec::<usize>::new());
+
+        // Prefers the start of a word over an earlier match inside one
+        assert_eq!(fuzzy_match("b", "a-ab-b").unwrap().indices, vec![5]);
+    }
+
+    #[test]
+    fn ranking() {
+        // A prefix beats a scattered match
+        assert!(score("re", "refactor") > score("re", "chore"));
+        assert!(score("fe", "feat") > score("fe", "refactor"));
+        // Consecutive beats spread out
+        assert!(score("imp", "improvement") > score("imp", "i-m-p"));
+    }
+}
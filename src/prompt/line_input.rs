@@ -0,0 +1,69 @@
+use crate::string::{self, next_word_grapheme, prev_word_grapheme, to_byte_offset, to_byte_range};
+use crossterm::event::KeyCode;
+
+/// A single line of text input with a cursor, shared by the prompts that take one line.
+#[derive(Debug, Default)]
+pub struct LineInput {
+    pub text: String,
+    /// The cursor position in graphemes.
+    pub cursor: usize,
+}
+
+impl LineInput {
+    pub fn new(text: impl Into<String>) -> Self {
+        let text = text.into();
+        let cursor = string::len(&text);
+        LineInput { text, cursor }
+    }
+
+    /// Applies an editing key, returning false for keys that aren't for editing.
+    pub fn handle_key(&mut self, code: KeyCode, ctrl: bool, alt: bool) -> bool {
+        match (code, ctrl, alt) {
+            (KeyCode::Char('a'), true, false) | (KeyCode::Home, false, false) => {
+                self.cursor = 0;
+            }
+            (KeyCode::Char('e'), true, false) | (KeyCode::End, false, false) => {
+                self.cursor = string::len(&self.text);
+            }
+            (KeyCode::Char('d'), true, false) | (KeyCode::Delete, false, false) => {
+                if self.cursor < string::len(&self.text) {
+                    let range = to_byte_range(&self.text, self.cursor);
+                    self.text.replace_range(range, "");
+                }
+            }
+            (KeyCode::Char(c), false, false) if c > '\x1F' => {
+                let offset = to_byte_offset(&self.text, self.cursor);
+                self.text.insert(offset, c);
+                self.cursor += 1;
+            }
+            (KeyCode::Left, false, false) => {
+                self.cursor = self.cursor.saturating_sub(1);
+            }
+            (KeyCode::Right, false, false) => {
+                self.cursor = std::cmp::min(self.cursor + 1, string::len(&self.text));
+            }
+            (KeyCode::Left, false, true) | (KeyCode::Char('b'), false, true) => {
+                self.cursor = prev_word_grapheme(&self.text, self.cursor);
+            }
+            (KeyCode::Right, false, true) | (KeyCode::Char('f'), false, true) => {
+                self.cursor = next_word_grapheme(&self.text, self.cursor);
+            }
+            (KeyCode::Backspace, false, true) => {
+                let end = to_byte_offset(&self.text, self.cursor);
+                let start = to_byte_offset(&self.text, prev_word_grapheme(&self.text, self.cursor));
+                self.text.replace_range(start..end, "");
+                self.cursor = string::len(&self.text[..start]);
+            }
+            (KeyCode::Backspace, false, false) => {
+                if self.cursor > 0 {
+                    let range = to_byte_range(&self.text, self.cursor - 1);
+                    self.text.replace_range(range, "");
+                    self.cursor -= 1;
+                }
+            }
+            _ => return false,
+        }
+
+        true
+    }
+}
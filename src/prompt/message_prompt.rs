@@ -1,198 +1,373 @@
-use crate::string::{self, next_word_grapheme, prev_word_grapheme, to_byte_offset, to_byte_range};
+use super::text_area::{Motion, TextArea};
+use crate::color::reset_display;
+use crate::string;
 use crate::Config;
 use crate::TermBuffer;
 use crossterm::{
+    self as ct,
     event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
-    style::{style, Color},
+    style::{style, Attribute, Color},
 };
+use std::ops::Range;
+use std::time::Duration;
+use unic_segment::Graphemes;
 
+/// The editor for the commit body. Lines are hard wrapped at `body_max_line_length` as they're
+/// typed.
 #[derive(Debug)]
 pub struct MessagePrompt<'a> {
     config: &'a Config,
-    input: Vec<String>,
-    cursor: (u16, u16),
+    text: TextArea,
+    /// The first screen row shown, when the text is taller than the terminal.
+    scroll: usize,
 }
 
 pub enum MessagePromptResult {
     Message(String),
+    /// Ctrl-O was pressed to continue editing this body in an external editor.
+    Editor(String),
     Escape,
     Terminate,
 }
 
+/// Splits a line into screen rows at most `width` columns wide, as ranges of graphemes. This
+/// soft wrap is by grapheme, so it never has to move the cursor, unlike the hard wrap at
+/// `body_max_line_length`. An empty line is one empty row.
+fn soft_wrap(line: &str, width: usize) -> Vec<Range<usize>> {
+    let mut rows = vec![];
+    let mut start = 0;
+    let mut columns = 0;
+
+    for (i, grapheme) in Graphemes::new(line).enumerate() {
+        let grapheme_width = string::grapheme_width(grapheme);
+        if columns + grapheme_width > width && i > start {
+            rows.push(start..i);
+            start = i;
+            columns = 0;
+        }
+        columns += grapheme_width;
+    }
+
+    rows.push(start..string::len(line));
+    rows
+}
+
+/// A screen row of the editor: a line number, and the graphemes of the line on the row.
+type Row = (usize, Range<usize>);
+
+/// A key, or text that was pasted.
+enum Input {
+    Key(KeyCode, bool, bool, bool),
+    Paste(String),
+}
+
+/// Reads the next key, along with any others already waiting. The crossterm version we use
+/// drops the markers of a bracketed paste, so a burst of text keys arriving together is
+/// taken to be a paste instead. That way a pasted Tab or Enter doesn't act as a key.
+///
+/// This is only a guess from timing. Keys typed fast enough to be read together, such as a
+/// letter and then Tab, are inserted as text rather than finishing the body. A paste the
+/// terminal delivers in pieces is the opposite case: a Tab or Enter alone at the end of a
+/// piece acts as a key. Both go away once crossterm reports pastes as their own event.
+fn read_inputs() -> Vec<Input> {
+    let mut keys = vec![];
+    loop {
+        if let Ok(Event::Key(key)) = event::read() {
+            keys.push(key);
+        }
+        if !event::poll(Duration::from_millis(0)).unwrap_or(false) {
+            break;
+        }
+    }
+
+    let is_text = |key: &KeyEvent| {
+        let plain = !key
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        plain && matches!(key.code, KeyCode::Char(_) | KeyCode::Enter | KeyCode::Tab)
+    };
+
+    if keys.len() > 1 && keys.iter().all(is_text) {
+        let text = keys
+            .iter()
+            .map(|key| match key.code {
+                KeyCode::Char(c) => c.to_string(),
+                KeyCode::Enter => "\n".to_string(),
+                _ => "    ".to_string(),
+            })
+            .collect();
+        return vec![Input::Paste(text)];
+    }
+
+    keys.into_iter()
+        .map(|KeyEvent { code, modifiers }| {
+            Input::Key(
+                code,
+                modifiers.contains(KeyModifiers::CONTROL),
+                modifiers.contains(KeyModifiers::SHIFT),
+                modifiers.contains(KeyModifiers::ALT),
+            )
+        })
+        .collect()
+}
+
 impl<'a> MessagePrompt<'a> {
     pub fn new(config: &'a Config) -> Self {
         MessagePrompt {
             config,
-            input: vec![String::new()],
-            cursor: (0, 0),
+            text: TextArea::new("", config.body_max_line_length),
+            scroll: 0,
+        }
+    }
+
+    /// Starts the editor with `body`, e.g. when coming back from the footers.
+    pub fn prefill(mut self, body: &str) -> Self {
+        self.text = TextArea::new(body, self.config.body_max_line_length);
+        self
+    }
+
+    /// Renders the graphemes `range` of line `y`, showing the selected part in reverse video.
+    fn render_row(&self, y: usize, range: Range<usize>) -> String {
+        let line = &self.text.lines()[y];
+        let (from, to) = self.text.selected_columns(y).unwrap_or((0, 0));
+
+        let mut row = String::new();
+        for (i, grapheme) in Graphemes::new(line)
+            .enumerate()
+            .skip(range.start)
+            .take(range.len())
+        {
+            if from <= i && i < to {
+                row.push_str(&style(grapheme).attribute(Attribute::Reverse).to_string());
+            } else {
+                row.push_str(grapheme);
+            }
         }
+
+        // A selected line break shows as a space at the end of the line
+        if range.end == string::len(line) && to > range.end {
+            row.push_str(&style(" ").attribute(Attribute::Reverse).to_string());
+        }
+
+        format!("{}{}", row, reset_display())
+    }
+
+    /// The screen rows of the text as line numbers and grapheme ranges, and the row and
+    /// column of the cursor.
+    fn layout(&self, width: usize) -> (Vec<Row>, (usize, usize)) {
+        let cursor = self.text.cursor();
+        let mut rows = vec![];
+        let mut cursor_at = (0, 0);
+
+        for (y, line) in self.text.lines().iter().enumerate() {
+            let line_rows = soft_wrap(line, width);
+            let last = line_rows.len() - 1;
+
+            for (i, range) in line_rows.into_iter().enumerate() {
+                // At the end of a row the cursor is shown at the start of the next, except
+                // on the last row of the line
+                if y == cursor.y && range.start <= cursor.x && (cursor.x < range.end || i == last) {
+                    let (before, _) = string::split_at(line, cursor.x);
+                    let (_, before) = string::split_at(before, range.start);
+                    cursor_at = (rows.len(), string::width(before));
+                }
+                rows.push((y, range));
+            }
+        }
+
+        (rows, cursor_at)
     }
 
     pub fn run(mut self) -> MessagePromptResult {
         let mut buffer = TermBuffer::new();
 
+        let mut pending = vec![];
         let mut first_iteration = true;
 
         loop {
-            let event = if first_iteration {
+            let input = if first_iteration {
                 first_iteration = false;
                 None
             } else {
-                match event::read() {
-                    Ok(Event::Key(KeyEvent { code, modifiers })) => Some((
-                        code,
-                        modifiers.contains(KeyModifiers::CONTROL),
-                        modifiers.contains(KeyModifiers::SHIFT),
-                        modifiers.contains(KeyModifiers::ALT),
-                    )),
-                    _ => continue,
+                if pending.is_empty() {
+                    pending = read_inputs();
+                    pending.reverse();
+                }
+                match pending.pop() {
+                    Some(input) => Some(input),
+                    None => continue,
                 }
             };
 
+            let event = match input {
+                Some(Input::Paste(text)) => {
+                    self.text.insert_str(&text);
+                    None
+                }
+                Some(Input::Key(code, ctrl, shift, alt)) => Some((code, ctrl, shift, alt)),
+                None => None,
+            };
+
             match event {
                 Some((KeyCode::Char('c'), true, false, false)) => {
                     return MessagePromptResult::Terminate;
                 }
+                Some((KeyCode::Tab, false, _, false)) => {
+                    return MessagePromptResult::Message(self.text.text());
+                }
+                Some((KeyCode::Char('o'), true, false, false)) => {
+                    return MessagePromptResult::Editor(self.text.text());
+                }
+                Some((KeyCode::Esc, false, _, false)) => {
+                    return MessagePromptResult::Escape;
+                }
+                Some((KeyCode::Char('\n'), _, false, true))
+                | Some((KeyCode::Char('\n'), true, false, _))
+                | Some((KeyCode::Enter, _, _, _)) => {
+                    self.text.newline();
+                }
+
+                // Ctrl-_ is reported as Ctrl-7
+                Some((KeyCode::Char('z'), true, false, false))
+                | Some((KeyCode::Char('_'), true, _, false))
+                | Some((KeyCode::Char('7'), true, _, false)) => {
+                    self.text.undo();
+                }
+                Some((KeyCode::Char('r'), true, false, false)) => {
+                    self.text.redo();
+                }
+
+                Some((KeyCode::Char('k'), true, false, false)) => {
+                    self.text.kill_to_end();
+                }
+                Some((KeyCode::Char('u'), true, false, false)) => {
+                    self.text.kill_to_start();
+                }
+                Some((KeyCode::Char('w'), true, false, false))
+                | Some((KeyCode::Backspace, false, _, true))
+                | Some((KeyCode::Char('\u{7f}'), false, _, true)) => {
+                    self.text.kill_word_or_selection();
+                }
+                Some((KeyCode::Char('w'), false, false, true)) => {
+                    self.text.copy_selection();
+                }
+                Some((KeyCode::Char('y'), true, false, false)) => {
+                    self.text.yank();
+                }
+                Some((KeyCode::Char('y'), false, false, true)) => {
+                    self.text.yank_pop();
+                }
+
                 Some((KeyCode::Char('a'), true, false, false)) => {
-                    self.cursor.0 = 0;
+                    self.text.move_to(Motion::LineStart, false);
                 }
                 Some((KeyCode::Char('e'), true, false, false)) => {
-                    let (_, y) = self.cursor;
-                    let line = self
-                        .input
-                        .get(y as usize)
-                        .expect("ctrl-e unable to find current line");
-                    self.cursor.0 = string::len(&line) as u16;
+                    self.text.move_to(Motion::LineEnd, false);
                 }
-                Some((KeyCode::Char('\n'), _, false, true))
-                | Some((KeyCode::Char('\n'), true, false, _)) => {
-                    self.input.push(String::new());
-                    self.cursor.1 += 1;
+                Some((KeyCode::Home, false, shift, false)) => {
+                    self.text.move_to(Motion::LineStart, shift);
                 }
-                Some((KeyCode::Enter, _, _, _)) => {
-                    return MessagePromptResult::Message(self.input.join("\n"));
+                Some((KeyCode::End, false, shift, false)) => {
+                    self.text.move_to(Motion::LineEnd, shift);
                 }
-                Some((KeyCode::Char(c), false, _, false)) if c > '\x1F' => {
-                    let (x, y) = self.cursor;
-                    let line = self.input.get_mut(y as usize).unwrap();
-                    line.insert(to_byte_offset(&line, x as usize), c);
-                    self.cursor.0 += 1;
-                }
-                Some((KeyCode::Left, false, _, false)) => {
-                    self.cursor.0 = self.cursor.0.saturating_sub(1);
-                }
-                Some((KeyCode::Right, false, _, false)) => {
-                    let (x, y) = self.cursor;
-                    let line = self.input.get_mut(y as usize).expect("KE::Right get_mut");
-                    if string::len(line) < x as usize + 1 {
-                        line.push(' ');
-                    }
-                    self.cursor.0 += 1;
-                }
-                Some((KeyCode::Left, false, _, true))
-                | Some((KeyCode::Char('b'), false, _, true)) => {
-                    let (x, y) = self.cursor;
-                    let line = &self.input.get(y as usize).expect("current line must exist");
-                    self.cursor.0 = prev_word_grapheme(line, x as usize) as u16;
-                }
-                Some((KeyCode::Right, false, _, true))
-                | Some((KeyCode::Char('f'), false, _, true)) => {
-                    let (x, y) = self.cursor;
-                    let line = &self.input.get(y as usize).expect("current line must exist");
-                    self.cursor.0 = next_word_grapheme(line, x as usize) as u16;
-                }
-                Some((KeyCode::Up, false, _, _)) => {
-                    self.cursor.1 = self.cursor.1.saturating_sub(1);
-                }
-                Some((KeyCode::Down, false, _, _)) => {
-                    let (_, y) = self.cursor;
-                    if (y as usize) + 1 >= self.input.len() {
-                        self.input.push(String::new());
-                    }
-                    self.cursor = (0, y + 1);
-                }
-                // Alt-Backspace deletes a word.
-                Some((KeyCode::Backspace, false, _, true))
-                | Some((KeyCode::Char('\u{7f}'), false, _, true)) => match self.cursor {
-                    (0, 0) => {}
-                    (0, y) => {
-                        self.input.remove(y as usize);
-                        self.cursor.1 -= 1;
-                        let line = &self.input[self.cursor.1 as usize];
-                        self.cursor.0 = string::len(line) as u16;
-                    }
-                    (x, y) => {
-                        let line = self
-                            .input
-                            .get_mut(y as usize)
-                            .expect("Alt-Backspace (x, y) get line y");
-                        let end = to_byte_offset(line, x as usize + 1);
-                        let start = to_byte_offset(line, prev_word_grapheme(line, x as usize));
-                        line.replace_range(start..end, "");
-
-                        self.cursor.0 = string::len(&line[..start]) as u16;
-                    }
-                },
-                Some((KeyCode::Backspace, false, _, false)) => match self.cursor {
-                    (0, 0) => {}
-                    (0, y) => {
-                        self.input.remove(y as usize);
-                        self.cursor.1 -= 1;
-                        let line = &self.input[self.cursor.1 as usize];
-                        self.cursor.0 = string::len(line) as u16;
-                    }
-                    (x, y) => {
-                        let line = self
-                            .input
-                            .get_mut(y as usize)
-                            .expect("Backspace (x, y) get line y");
-
-                        if x as usize >= string::len(line) {
-                            line.pop();
-                        } else {
-                            line.replace_range(to_byte_range(line as &str, x as usize - 1), "");
-                        }
-                        self.cursor.0 -= 1;
-                    }
-                },
-                Some((KeyCode::Char('d'), true, _, false)) => {
-                    let line = &mut self.input[self.cursor.1 as usize];
-
-                    line.replace_range(to_byte_range(line, self.cursor.0 as usize), "");
+                Some((KeyCode::Left, false, shift, false)) => {
+                    self.text.move_to(Motion::Left, shift);
                 }
-                Some((KeyCode::Esc, false, _, false)) => {
-                    return MessagePromptResult::Escape;
+                Some((KeyCode::Right, false, shift, false)) => {
+                    self.text.move_to(Motion::Right, shift);
+                }
+                Some((KeyCode::Up, false, shift, _)) => {
+                    self.text.move_to(Motion::Up, shift);
+                }
+                Some((KeyCode::Down, false, shift, _)) => {
+                    self.text.move_to(Motion::Down, shift);
+                }
+                Some((KeyCode::Left, false, shift, true))
+                | Some((KeyCode::Char('b'), false, shift, true)) => {
+                    self.text.move_to(Motion::WordLeft, shift);
+                }
+                Some((KeyCode::Right, false, shift, true))
+                | Some((KeyCode::Char('f'), false, shift, true)) => {
+                    self.text.move_to(Motion::WordRight, shift);
+                }
+
+                Some((KeyCode::Backspace, false, _, false)) => {
+                    self.text.delete_backward();
+                }
+                Some((KeyCode::Char('d'), true, _, false))
+                | Some((KeyCode::Delete, false, _, false)) => {
+                    self.text.delete_forward();
+                }
+                Some((KeyCode::Char(c), false, _, false)) if c > '\x1F' => {
+                    self.text.insert_char(c);
                 }
                 None => {}
                 _ => continue,
             };
 
-            let (x, y) = self.cursor;
-            let instructions = "Commit message (arrow keys for multiple lines):";
-            let divider = "-".repeat(instructions.len());
-            buffer.push_line(instructions);
+            let (term_width, term_height) = ct::terminal::size().unwrap_or((80, 24));
+            // Rows are kept short of the last column, so the terminal never wraps them itself
+            let width = std::cmp::max(term_width as usize, 2) - 1;
+            // Leave room for the two lines above the editor and one for the cursor to spare
+            let height = std::cmp::max(term_height as usize, 4) - 3;
+
+            let (rows, (cursor_row, cursor_x)) = self.layout(width);
+            if cursor_row < self.scroll {
+                self.scroll = cursor_row;
+            } else if cursor_row >= self.scroll + height {
+                self.scroll = cursor_row + 1 - height;
+            }
+            self.scroll = std::cmp::min(self.scroll, rows.len().saturating_sub(height));
+
+            let instructions =
+                "Commit body (optional, Enter for a new line, Ctrl-O for your editor, Tab when done):";
+            let instructions = string::truncate_with_ellipsis(instructions, width);
+            buffer.push_line(instructions.as_ref());
+
+            let divider = if rows.len() > height {
+                let position = format!(
+                    " rows {}-{} of {} ",
+                    self.scroll + 1,
+                    std::cmp::min(self.scroll + height, rows.len()),
+                    rows.len()
+                );
+                let rest = string::width(&instructions).saturating_sub(position.len() + 2);
+                format!(
+                    "--{}{}{}",
+                    style(position).with(Color::DarkGrey),
+                    reset_display(),
+                    "-".repeat(rest)
+                )
+            } else {
+                "-".repeat(string::width(&instructions))
+            };
             buffer.push_line(divider);
 
             // The offset for where the editor begins, i.e. the number of push_line calls above.
             let editor_y = 2;
 
-            for (i, line) in self.input.iter().enumerate() {
-                if i == 0 && line.len() > 50 {
-                    let (good, bad) = crate::string::split_at(&line, 50);
-                    buffer.push_line(format!(
-                        "{}{}{}",
-                        good,
-                        style(bad).with(Color::Red),
-                        crate::color::reset_display(),
-                    ));
-                } else {
-                    buffer.push_line(line.to_string());
-                }
+            for (y, range) in rows.into_iter().skip(self.scroll).take(height) {
+                buffer.push_line(self.render_row(y, range));
             }
 
-            buffer.set_next_cursor((x, y + editor_y));
+            let cursor_y = (cursor_row - self.scroll) as u16 + editor_y;
+            buffer.set_next_cursor((cursor_x as u16, cursor_y));
             buffer.render_frame();
             buffer.flush();
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::soft_wrap;
+
+    #[test]
+    fn soft_wraps_by_width() {
+        assert_eq!(soft_wrap("", 4), vec![0..0]);
+        assert_eq!(soft_wrap("abcdefghij", 4), vec![0..4, 4..8, 8..10]);
+        // Wide characters take two columns, and aren't split across rows
+        assert_eq!(soft_wrap("a日本語", 4), vec![0..2, 2..4]);
+        assert_eq!(soft_wrap("e\u{301}e\u{301}e\u{301}", 2), vec![0..2, 2..3]);
+    }
+}
@@ -0,0 +1,277 @@
+use crate::color::reset_display;
+use crate::lint::{self, Diagnostic, Severity};
+use crate::string;
+use crate::Commit;
+use crate::Config;
+use crate::TermBuffer;
+use crossterm::{
+    self as ct,
+    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    style::{style, Color},
+};
+
+/// At most this many files are listed, so the message and options stay on screen.
+const MAX_FILES: usize = 5;
+
+/// A prompt that can be returned to from the review.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ReviewEdit {
+    Files,
+    Type,
+    Scope,
+    Subject,
+    Body,
+    Footers,
+}
+
+impl ReviewEdit {
+    fn label(self) -> &'static str {
+        match self {
+            ReviewEdit::Files => "Edit the files",
+            ReviewEdit::Type => "Edit the type",
+            ReviewEdit::Scope => "Edit the scope",
+            ReviewEdit::Subject => "Edit the subject",
+            ReviewEdit::Body => "Edit the body",
+            ReviewEdit::Footers => "Edit the footers",
+        }
+    }
+}
+
+/// Shows the message as it will be committed, with the files, the extra git arguments and
+/// any lint problems, before anything is committed.
+#[derive(Debug)]
+pub struct ReviewPrompt<'a> {
+    message: String,
+    diagnostics: Vec<Diagnostic>,
+    /// The files that will be added, or otherwise those already staged.
+    files: &'a [String],
+    adding_files: bool,
+    git_args: &'a [String],
+    /// The prompts that can be returned to, in the order they're listed.
+    edits: Vec<ReviewEdit>,
+    focused_index: usize,
+}
+
+pub enum ReviewPromptResult {
+    Commit,
+    Edit(ReviewEdit),
+    Abort,
+    Escape,
+    Terminate,
+}
+
+/// An entry in the list of options.
+#[derive(Clone, Copy)]
+enum Choice {
+    Commit,
+    Edit(ReviewEdit),
+    Abort,
+}
+
+impl<'a> ReviewPrompt<'a> {
+    pub fn new(config: &Config, commit: &Commit) -> Self {
+        let message = commit.build_message();
+        let diagnostics = lint::lint(&message, config);
+
+        ReviewPrompt {
+            message,
+            diagnostics,
+            files: &[],
+            adding_files: false,
+            git_args: &[],
+            edits: vec![],
+            focused_index: 0,
+        }
+    }
+
+    /// The files committed. When `adding` is set, they were chosen in the files prompt and
+    /// will be added first.
+    pub fn files(mut self, files: &'a [String], adding: bool) -> Self {
+        self.files = files;
+        self.adding_files = adding;
+        self
+    }
+
+    pub fn git_args(mut self, git_args: &'a [String]) -> Self {
+        self.git_args = git_args;
+        self
+    }
+
+    /// The prompts offered for editing. Ones that were skipped, e.g. with `-t`, shouldn't be
+    /// in here.
+    pub fn edits(mut self, edits: Vec<ReviewEdit>) -> Self {
+        self.edits = edits;
+        self
+    }
+
+    fn choices(&self) -> Vec<Choice> {
+        std::iter::once(Choice::Commit)
+            .chain(self.edits.iter().copied().map(Choice::Edit))
+            .chain(std::iter::once(Choice::Abort))
+            .collect()
+    }
+
+    pub fn run(mut self) -> ReviewPromptResult {
+        let mut buffer = TermBuffer::new();
+
+        let mut first_iteration = true;
+
+        loop {
+            let event = if first_iteration {
+                first_iteration = false;
+                None
+            } else {
+                match event::read() {
+                    Ok(Event::Key(KeyEvent { code, modifiers })) => Some((
+                        code,
+                        modifiers.contains(KeyModifiers::CONTROL),
+                        modifiers.contains(KeyModifiers::SHIFT),
+                        modifiers.contains(KeyModifiers::ALT),
+                    )),
+                    _ => continue,
+                }
+            };
+
+            let choices = self.choices();
+
+            match event {
+                Some((KeyCode::Char('c'), true, false, false)) => {
+                    return ReviewPromptResult::Terminate;
+                }
+                Some((KeyCode::Enter, false, _, false)) => {
+                    return match choices[self.focused_index] {
+                        Choice::Commit => ReviewPromptResult::Commit,
+                        Choice::Edit(edit) => ReviewPromptResult::Edit(edit),
+                        Choice::Abort => ReviewPromptResult::Abort,
+                    };
+                }
+                Some((KeyCode::Esc, false, _, false)) => {
+                    return ReviewPromptResult::Escape;
+                }
+                Some((KeyCode::Up, false, _, false)) => {
+                    self.focused_index = self.focused_index.saturating_sub(1);
+                }
+                Some((KeyCode::Down, false, _, false)) => {
+                    self.focused_index = std::cmp::min(self.focused_index + 1, choices.len() - 1);
+                }
+                None => {}
+                _ => continue,
+            };
+
+            let (term_width, term_height) = ct::terminal::size().unwrap_or((80, 24));
+            // Lines are kept short of the last column, so the terminal never wraps them itself
+            let width = std::cmp::max(term_width as usize, 2) - 1;
+
+            let instructions = "Review the commit (Enter to choose, Esc to go back):";
+            buffer.push_line(string::truncate_with_ellipsis(instructions, width).as_ref());
+            buffer.push_line("-".repeat(std::cmp::min(string::width(instructions), width)));
+
+            let mut details = vec![];
+            if !self.files.is_empty() {
+                details.push(if self.adding_files {
+                    "Files to add:".to_string()
+                } else {
+                    "Staged files:".to_string()
+                });
+                for file in self.files.iter().take(MAX_FILES) {
+                    let file = string::truncate_with_ellipsis(file, width.saturating_sub(2));
+                    details.push(format!(
+                        "  {}{}",
+                        style(file).with(Color::Green),
+                        reset_display()
+                    ));
+                }
+                if self.files.len() > MAX_FILES {
+                    details.push(format!("  … and {} more", self.files.len() - MAX_FILES));
+                }
+            }
+            if !self.git_args.is_empty() {
+                let git_args = format!("Git arguments: {}", self.git_args.join(" "));
+                details.push(string::truncate_with_ellipsis(&git_args, width).into_owned());
+            }
+            for diagnostic in &self.diagnostics {
+                let color = match diagnostic.severity {
+                    Severity::Error => Color::Red,
+                    Severity::Warning => Color::Yellow,
+                };
+                let text =
+                    string::truncate_with_ellipsis(&diagnostic.message, width.saturating_sub(2));
+                details.push(format!(
+                    "{} {}{}",
+                    style(diagnostic.severity).with(color),
+                    text,
+                    reset_display()
+                ));
+            }
+
+            // The message gets whatever room the rest leaves, with a blank line before the
+            // details and the options, and one for the cursor to spare. Long lines are wrapped
+            // rather than cut off, so nothing in the message is hidden.
+            let rows: Vec<(usize, &str)> = self
+                .message
+                .lines()
+                .enumerate()
+                .flat_map(|(y, line)| {
+                    string::wrap(line, width)
+                        .into_iter()
+                        .map(move |row| (y, row))
+                })
+                .collect();
+            let used = buffer.lines() as usize + details.len() + choices.len() + 3;
+            let room = std::cmp::max((term_height as usize).saturating_sub(used), 2);
+            let shown = if rows.len() > room {
+                room - 1
+            } else {
+                rows.len()
+            };
+
+            for &(y, row) in rows.iter().take(shown) {
+                let color = if y == 0 {
+                    crate::color::theme_user_input()
+                } else {
+                    Color::Reset
+                };
+                buffer.push_line(format!("{}{}", style(row).with(color), reset_display()));
+            }
+            if shown < rows.len() {
+                buffer.push_line(format!(
+                    "{}{}",
+                    style(format!("… {} more rows", rows.len() - shown)).with(Color::DarkGrey),
+                    reset_display()
+                ));
+            }
+
+            if !details.is_empty() {
+                buffer.push_line("");
+                for line in details {
+                    buffer.push_line(line);
+                }
+            }
+
+            buffer.push_line("");
+            let y_offset = buffer.lines();
+            for (i, choice) in choices.iter().enumerate() {
+                let label = match choice {
+                    Choice::Commit => "Commit",
+                    Choice::Edit(edit) => edit.label(),
+                    Choice::Abort => "Abort",
+                };
+                let (marker, color) = if i == self.focused_index {
+                    ("*", Color::Blue)
+                } else {
+                    ("-", Color::Reset)
+                };
+                buffer.push_line(format!(
+                    "{} {}{}",
+                    style(marker).with(color),
+                    style(label).with(color),
+                    reset_display()
+                ));
+            }
+
+            buffer.set_next_cursor((0, y_offset + self.focused_index as u16));
+            buffer.render_frame();
+            buffer.flush();
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use crate::color::reset_display;
+use crate::string;
 use crate::Config;
 use crate::TermBuffer;
 use crossterm::{
@@ -6,12 +8,18 @@ use crossterm::{
     style::{style, Color},
 };
 
+/// How many suggestions to list below the header.
+const MAX_SUGGESTIONS: usize = 6;
+
 #[derive(Debug)]
 pub struct ScopePrompt<'a> {
     config: &'a Config,
+    /// Scopes to offer, best first.
+    suggestions: &'a [String],
     input: String,
     selected_index: u16,
     ty: &'a str,
+    /// The cursor, in graphemes, since suggestions may contain any characters.
     x_offset: u16,
     finished: bool,
 }
@@ -23,9 +31,10 @@ pub enum ScopePromptResult {
 }
 
 impl<'a> ScopePrompt<'a> {
-    pub fn new(config: &'a Config, ty: &'a str) -> Self {
+    pub fn new(config: &'a Config, ty: &'a str, suggestions: &'a [String]) -> Self {
         ScopePrompt {
             config,
+            suggestions,
             input: Default::default(),
             selected_index: 0,
             ty,
@@ -34,6 +43,52 @@ impl<'a> ScopePrompt<'a> {
         }
     }
 
+    /// Starts the prompt with `scope` already entered.
+    pub fn prefill(mut self, scope: &str) -> Self {
+        self.input = scope.to_string();
+        self.x_offset = string::len(&self.input) as u16;
+        self
+    }
+
+    /// The byte offset where the scope being typed starts, after any earlier scopes in a
+    /// list like `api,ui`.
+    fn segment_start(&self) -> usize {
+        self.input.rfind(&[',', '|'][..]).map_or(0, |i| i + 1)
+    }
+
+    /// Suggestions containing the scope being typed, with those starting with it first.
+    /// Scopes already in the list are left out.
+    fn filter_suggestions(&self) -> Vec<&str> {
+        let start = self.segment_start();
+        let segment = &self.input[start..];
+        let earlier: Vec<&str> = self.input[..start].split(&[',', '|'][..]).collect();
+
+        let candidates = self
+            .suggestions
+            .iter()
+            .map(String::as_str)
+            .filter(|scope| !earlier.contains(scope));
+
+        let (mut matches, contains): (Vec<&str>, Vec<&str>) = candidates
+            .filter(|scope| scope.contains(segment))
+            .partition(|scope| scope.starts_with(segment));
+        matches.extend(contains);
+        matches
+    }
+
+    /// Replaces the scope being typed with the selected suggestion.
+    fn complete(&mut self) {
+        let suggestion = match self.filter_suggestions().get(self.selected_index as usize) {
+            Some(suggestion) => suggestion.to_string(),
+            None => return,
+        };
+
+        let start = self.segment_start();
+        self.input.replace_range(start.., &suggestion);
+        self.x_offset = string::len(&self.input) as u16;
+        self.selected_index = 0;
+    }
+
     pub fn run(mut self) -> ScopePromptResult {
         let mut buffer = TermBuffer::new();
 
@@ -77,34 +132,44 @@ impl<'a> ScopePrompt<'a> {
                         || c == ','
                         || c == '|';
                     if accept {
-                        self.x_offset += 1;
+                        let at = string::to_byte_offset(&self.input, self.x_offset as usize);
+                        self.input.insert(at, c.to_ascii_lowercase());
 
-                        self.input
-                            .insert(self.x_offset as usize - 1, c.to_ascii_lowercase());
+                        self.x_offset += 1;
+                        self.selected_index = 0;
                     }
                 }
                 Some((KeyCode::Left, false, _, false)) => {
                     self.x_offset = self.x_offset.saturating_sub(1);
                 }
                 Some((KeyCode::Right, false, _, false)) => {
-                    if (self.x_offset as usize) < self.input.len() {
+                    if (self.x_offset as usize) < string::len(&self.input) {
                         self.x_offset += 1;
                     }
                 }
                 Some((KeyCode::Backspace, false, _, false)) => {
-                    let offset = self.x_offset as usize;
-                    let len = self.input.len();
-                    if len > 0 && offset < len - 1 {
-                        self.input.remove(offset - 1);
-                        self.x_offset -= 1;
-                    } else if len > 0 {
-                        self.input.pop();
+                    if self.x_offset > 0 {
+                        let removed =
+                            string::to_byte_range(&self.input, self.x_offset as usize - 1);
+                        self.input.replace_range(removed, "");
                         self.x_offset -= 1;
                     }
                 }
                 Some((KeyCode::Esc, false, _, false)) => {
                     return ScopePromptResult::Escape;
                 }
+                Some((KeyCode::Tab, false, _, false)) => {
+                    self.complete();
+                }
+                Some((KeyCode::Up, false, _, false)) => {
+                    self.selected_index = self.selected_index.saturating_sub(1);
+                }
+                Some((KeyCode::Down, false, _, false)) => {
+                    let shown = std::cmp::min(self.filter_suggestions().len(), MAX_SUGGESTIONS);
+                    if (self.selected_index as usize) + 1 < shown {
+                        self.selected_index += 1;
+                    }
+                }
                 None => {}
                 _ => continue,
             };
@@ -126,11 +191,11 @@ impl<'a> ScopePrompt<'a> {
                 });
             }
 
-            let offset = self.x_offset as usize;
-            cursor_x +=
-                figlet.write_to_buf_color(&(self.input.as_str())[0..offset], &mut lines[..], |s| {
-                    style(s).with(Color::Green).to_string()
-                });
+            let (before_cursor, after_cursor) =
+                string::split_at(&self.input, self.x_offset as usize);
+            cursor_x += figlet.write_to_buf_color(before_cursor, &mut lines[..], |s| {
+                style(s).with(Color::Green).to_string()
+            });
 
             let mut fig_width = cursor_x;
 
@@ -142,10 +207,9 @@ impl<'a> ScopePrompt<'a> {
                 });
             }
 
-            fig_width +=
-                figlet.write_to_buf_color(&(self.input.as_str())[offset..], &mut lines[..], |s| {
-                    style(s).with(Color::Green).to_string()
-                });
+            fig_width += figlet.write_to_buf_color(after_cursor, &mut lines[..], |s| {
+                style(s).with(Color::Green).to_string()
+            });
 
             if show_parens {
                 fig_width += figlet.write_to_buf_color(")", &mut lines[..], |s| {
@@ -171,25 +235,37 @@ impl<'a> ScopePrompt<'a> {
 
                 write!(line, "{}", style(&self.ty).with(Color::Blue)).unwrap();
                 write!(line, "{}", style("(").with(Color::Grey)).unwrap();
-                write!(
-                    line,
-                    "{}",
-                    style(&(self.input.as_str())[0..offset]).with(Color::Green)
-                )
-                .unwrap();
+                write!(line, "{}", style(before_cursor).with(Color::Green)).unwrap();
 
                 if !self.finished {
                     write!(line, "{}", style("_").with(Color::Grey)).unwrap();
                 }
                 write!(line, "{}", style(")").with(Color::Grey)).unwrap();
 
-                cursor_x = self.ty.len() + 1 + self.input.len();
+                cursor_x = string::width(self.ty) + 1 + string::width(before_cursor);
             }
 
             for line in lines {
                 buffer.push_line(line);
             }
 
+            if !self.finished {
+                let suggestions = self.filter_suggestions();
+                if !suggestions.is_empty() {
+                    buffer.push_line("");
+                }
+
+                for (i, scope) in suggestions.into_iter().take(MAX_SUGGESTIONS).enumerate() {
+                    let line_content = if i as u16 == self.selected_index {
+                        style(["*", " ", scope].concat()).with(Color::Blue)
+                    } else {
+                        style(["-", " ", scope].concat()).with(Color::Reset)
+                    };
+
+                    buffer.push_line(format!("{}{}", line_content, reset_display()));
+                }
+            }
+
             buffer.set_next_cursor((cursor_x as u16, cursor_y));
             buffer.render_frame();
             buffer.flush();
@@ -0,0 +1,153 @@
+use super::line_input::LineInput;
+use crate::color::reset_display;
+use crate::string;
+use crate::Config;
+use crate::TermBuffer;
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    style::{style, Color},
+};
+
+/// Prompts for the first line of the message, after the `type(scope): ` prefix, counting
+/// the whole header against `header_max_length`.
+#[derive(Debug)]
+pub struct SubjectPrompt<'a> {
+    config: &'a Config,
+    /// The `type(scope): ` part of the header, shown before the input.
+    prefix: &'a str,
+    input: LineInput,
+    error: Option<String>,
+}
+
+pub enum SubjectPromptResult {
+    Subject(String),
+    Escape,
+    Terminate,
+}
+
+impl<'a> SubjectPrompt<'a> {
+    pub fn new(config: &'a Config, prefix: &'a str) -> Self {
+        SubjectPrompt {
+            config,
+            prefix,
+            input: LineInput::default(),
+            error: None,
+        }
+    }
+
+    /// Starts the prompt with `subject` already entered, e.g. when coming back from the body.
+    pub fn prefill(mut self, subject: &str) -> Self {
+        self.input = LineInput::new(subject);
+        self
+    }
+
+    fn header_len(&self) -> usize {
+        string::len(self.prefix) + string::len(&self.input.text)
+    }
+
+    /// Why the subject can't be submitted yet, if it can't.
+    fn check(&self) -> Result<(), String> {
+        let limit = self.config.header_max_length;
+
+        if self.input.text.trim().is_empty() {
+            Err("The subject may not be empty".into())
+        } else if self.header_len() > limit {
+            Err(format!(
+                "The first line is {} characters, but the limit is {}",
+                self.header_len(),
+                limit
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn run(mut self) -> SubjectPromptResult {
+        let mut buffer = TermBuffer::new();
+
+        let mut first_iteration = true;
+
+        loop {
+            let event = if first_iteration {
+                first_iteration = false;
+                None
+            } else {
+                match event::read() {
+                    Ok(Event::Key(KeyEvent { code, modifiers })) => Some((
+                        code,
+                        modifiers.contains(KeyModifiers::CONTROL),
+                        modifiers.contains(KeyModifiers::SHIFT),
+                        modifiers.contains(KeyModifiers::ALT),
+                    )),
+                    _ => continue,
+                }
+            };
+
+            match event {
+                Some((KeyCode::Char('c'), true, false, false)) => {
+                    return SubjectPromptResult::Terminate;
+                }
+                Some((KeyCode::Enter, false, _, false)) => match self.check() {
+                    Ok(()) => {
+                        return SubjectPromptResult::Subject(self.input.text.trim().to_string());
+                    }
+                    Err(err) => self.error = Some(err),
+                },
+                Some((KeyCode::Esc, false, _, false)) => {
+                    return SubjectPromptResult::Escape;
+                }
+                Some((code, ctrl, _, alt)) => {
+                    if !self.input.handle_key(code, ctrl, alt) {
+                        continue;
+                    }
+                    self.error = None;
+                }
+                None => {}
+            };
+
+            let limit = self.config.header_max_length;
+            let len = self.header_len();
+            let counter_color = if len > limit {
+                Color::Red
+            } else if len * 10 > limit * 9 {
+                Color::Yellow
+            } else {
+                Color::DarkGrey
+            };
+
+            let instructions = "Commit subject:";
+            buffer.push_line(format!(
+                "{} {}{}",
+                instructions,
+                style(format!("{}/{}", len, limit)).with(counter_color),
+                reset_display()
+            ));
+            buffer.push_line("-".repeat(instructions.len()));
+
+            // Characters past the limit are shown in red
+            let allowed = limit.saturating_sub(string::len(self.prefix));
+            let (good, bad) = string::split_at(&self.input.text, allowed);
+            buffer.push_line(format!(
+                "{}{}{}{}",
+                style(self.prefix).with(Color::Blue),
+                style(good).with(crate::color::theme_user_input()),
+                style(bad).with(Color::Red),
+                reset_display()
+            ));
+
+            if let Some(error) = &self.error {
+                buffer.push_line(format!(
+                    "{}{}",
+                    style(error).with(Color::Red),
+                    reset_display()
+                ));
+            }
+
+            let (before_cursor, _) = string::split_at(&self.input.text, self.input.cursor);
+            let x = string::width(self.prefix) + string::width(before_cursor);
+            buffer.set_next_cursor((x as u16, 2));
+            buffer.render_frame();
+            buffer.flush();
+        }
+    }
+}
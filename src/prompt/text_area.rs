@@ -0,0 +1,676 @@
+//! The editing core of the body prompt: multi-line text with a cursor, a selection, undo
+//! history, and a kill ring.
+
+use crate::string::{self, to_byte_offset};
+use unic_segment::Graphemes;
+
+/// How many kills the kill ring keeps.
+const KILL_RING_SIZE: usize = 16;
+
+/// A position in the text, in graphemes. Ordered by line, then column.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Pos {
+    pub y: usize,
+    pub x: usize,
+}
+
+impl Pos {
+    pub fn new(x: usize, y: usize) -> Self {
+        Pos { y, x }
+    }
+}
+
+/// The state restored by undo and redo.
+#[derive(Debug, Clone)]
+struct Snapshot {
+    lines: Vec<String>,
+    cursor: Pos,
+}
+
+/// The last edit, so runs of the same edit are undone together and consecutive kills are
+/// joined in the kill ring.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Edit {
+    Typing,
+    Deleting,
+    KillForward,
+    KillBackward,
+    /// A yank of the kill ring entry at the index, between the positions.
+    Yank(usize, Pos, Pos),
+    Other,
+}
+
+#[derive(Debug)]
+pub struct TextArea {
+    lines: Vec<String>,
+    cursor: Pos,
+    /// Where the selection started, if there is one. It extends to the cursor.
+    anchor: Option<Pos>,
+    /// The column to return to when moving up and down through shorter lines.
+    goal_x: Option<usize>,
+    /// Lines are hard wrapped at this many graphemes as text is added, unless it's 0.
+    wrap: usize,
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+    last_edit: Option<Edit>,
+    /// Killed text, newest last.
+    kill_ring: Vec<String>,
+}
+
+impl TextArea {
+    pub fn new(text: &str, wrap: usize) -> Self {
+        let mut lines: Vec<String> = text.lines().map(String::from).collect();
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+
+        let y = lines.len() - 1;
+        let cursor = Pos::new(string::len(&lines[y]), y);
+
+        TextArea {
+            lines,
+            cursor,
+            anchor: None,
+            goal_x: None,
+            wrap,
+            undo: vec![],
+            redo: vec![],
+            last_edit: None,
+            kill_ring: vec![],
+        }
+    }
+
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    pub fn cursor(&self) -> Pos {
+        self.cursor
+    }
+
+    /// The text, without trailing white-space on each line or blank lines at either end.
+    pub fn text(&self) -> String {
+        let lines: Vec<&str> = self.lines.iter().map(|line| line.trim_end()).collect();
+        lines.join("\n").trim_matches('\n').to_string()
+    }
+
+    /// The start and end of the selection, if anything is selected.
+    pub fn selection(&self) -> Option<(Pos, Pos)> {
+        let anchor = self.anchor.filter(|anchor| *anchor != self.cursor)?;
+        Some((
+            std::cmp::min(anchor, self.cursor),
+            std::cmp::max(anchor, self.cursor),
+        ))
+    }
+
+    /// The graphemes of line `y` that are selected.
+    pub fn selected_columns(&self, y: usize) -> Option<(usize, usize)> {
+        let (start, end) = self.selection()?;
+        if y < start.y || y > end.y {
+            return None;
+        }
+
+        let from = if y == start.y { start.x } else { 0 };
+        let to = if y == end.y {
+            end.x
+        } else {
+            // Include the line break in the selection
+            self.line_len(y) + 1
+        };
+        Some((from, to))
+    }
+
+    fn line_len(&self, y: usize) -> usize {
+        string::len(&self.lines[y])
+    }
+
+    fn byte_offset(&self, pos: Pos) -> usize {
+        to_byte_offset(&self.lines[pos.y], pos.x)
+    }
+
+    fn text_between(&self, start: Pos, end: Pos) -> String {
+        if start.y == end.y {
+            return self.lines[start.y][self.byte_offset(start)..self.byte_offset(end)].to_string();
+        }
+
+        let mut text = self.lines[start.y][self.byte_offset(start)..].to_string();
+        for line in &self.lines[start.y + 1..end.y] {
+            text.push('\n');
+            text.push_str(line);
+        }
+        text.push('\n');
+        text.push_str(&self.lines[end.y][..self.byte_offset(end)]);
+        text
+    }
+
+    /// Removes the text between the positions, leaving the cursor at `start`.
+    fn remove_between(&mut self, start: Pos, end: Pos) -> String {
+        let removed = self.text_between(start, end);
+
+        let tail = self.lines[end.y][self.byte_offset(end)..].to_string();
+        let start_byte = self.byte_offset(start);
+        self.lines[start.y].truncate(start_byte);
+        self.lines[start.y].push_str(&tail);
+        self.lines.drain(start.y + 1..=end.y);
+
+        self.cursor = start;
+        removed
+    }
+
+    /// Inserts `text` at the cursor, leaving the cursor after it.
+    fn insert(&mut self, text: &str) {
+        let at = self.byte_offset(self.cursor);
+        let tail = self.lines[self.cursor.y].split_off(at);
+
+        let mut parts = text.split('\n');
+        if let Some(first) = parts.next() {
+            self.lines[self.cursor.y].push_str(first);
+        }
+        for part in parts {
+            self.cursor.y += 1;
+            self.lines.insert(self.cursor.y, part.to_string());
+        }
+
+        self.cursor.x = self.line_len(self.cursor.y);
+        self.lines[self.cursor.y].push_str(&tail);
+    }
+
+    /// Saves the current state for undo before an edit. Runs of typing or deleting are
+    /// saved once, so they're undone together.
+    fn checkpoint(&mut self, edit: Edit) {
+        let continues = matches!(
+            (self.last_edit, edit),
+            (Some(Edit::Typing), Edit::Typing) | (Some(Edit::Deleting), Edit::Deleting)
+        );
+
+        if !continues {
+            self.undo.push(Snapshot {
+                lines: self.lines.clone(),
+                cursor: self.cursor,
+            });
+            self.redo.clear();
+        }
+
+        self.last_edit = Some(edit);
+        self.goal_x = None;
+    }
+
+    /// Removes the selection, returning its text, if anything is selected.
+    fn take_selection(&mut self) -> Option<String> {
+        let (start, end) = self.selection()?;
+        self.anchor = None;
+        Some(self.remove_between(start, end))
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        // A space ends a run of typing, so undo goes back a word at a time
+        let edit = if c == ' ' { Edit::Other } else { Edit::Typing };
+        self.checkpoint(edit);
+        self.take_selection();
+        self.insert_wrapped(c.encode_utf8(&mut [0; 4]));
+    }
+
+    /// Inserts text that may span lines, e.g. a paste, as a single edit.
+    pub fn insert_str(&mut self, text: &str) {
+        self.checkpoint(Edit::Other);
+        self.take_selection();
+        self.insert_wrapped(text);
+    }
+
+    /// Inserts `text` and hard wraps the lines it's on. Returns where the text starts, which
+    /// wrapping may have moved.
+    fn insert_wrapped(&mut self, text: &str) -> Pos {
+        let mut start = self.cursor;
+        self.insert(text);
+
+        let mut y = start.y;
+        while y <= self.cursor.y {
+            self.wrap_line(y, &mut start);
+            y += 1;
+        }
+        start
+    }
+
+    pub fn newline(&mut self) {
+        self.checkpoint(Edit::Other);
+        self.take_selection();
+        self.insert("\n");
+    }
+
+    /// Hard wraps line `y` at the wrap width, moving the words past it to a new line below,
+    /// along with the cursor and `mark` if they were past the break.
+    fn wrap_line(&mut self, y: usize, mark: &mut Pos) {
+        if self.wrap == 0 || self.line_len(y) <= self.wrap {
+            return;
+        }
+
+        // Break at the last space within the width, or mid-word if there isn't one
+        let line = &self.lines[y];
+        let limit = to_byte_offset(line, self.wrap + 1);
+        let (split, skip) = match line[..limit].rfind(' ') {
+            Some(i) if i > 0 => (i, 1),
+            _ => (to_byte_offset(line, self.wrap), 0),
+        };
+
+        let rest = line[split + skip..].to_string();
+        let head_len = string::len(&line[..split]);
+        self.lines[y].truncate(split);
+        self.lines.insert(y + 1, rest);
+
+        let shift = |pos: Pos| {
+            if pos.y == y && pos.x > head_len {
+                Pos::new(pos.x - head_len - skip, y + 1)
+            } else if pos.y > y {
+                Pos::new(pos.x, pos.y + 1)
+            } else {
+                pos
+            }
+        };
+        self.cursor = shift(self.cursor);
+        *mark = shift(*mark);
+
+        self.wrap_line(y + 1, mark);
+    }
+
+    /// Deletes the selection, or the grapheme before the cursor, joining lines at the start
+    /// of one.
+    pub fn delete_backward(&mut self) {
+        self.checkpoint(Edit::Deleting);
+        if self.take_selection().is_some() {
+            return;
+        }
+
+        let end = self.cursor;
+        let start = match end {
+            Pos { x: 0, y: 0 } => return,
+            Pos { x: 0, y } => Pos::new(self.line_len(y - 1), y - 1),
+            Pos { x, y } => Pos::new(x - 1, y),
+        };
+        self.remove_between(start, end);
+    }
+
+    /// Deletes the selection, or the grapheme after the cursor, joining the next line at the
+    /// end of one.
+    pub fn delete_forward(&mut self) {
+        self.checkpoint(Edit::Deleting);
+        if self.take_selection().is_some() {
+            return;
+        }
+
+        let start = self.cursor;
+        let end = match self.pos_right(start) {
+            Some(end) => end,
+            None => return,
+        };
+        self.remove_between(start, end);
+    }
+
+    /// Adds killed text to the kill ring. Consecutive kills in the same direction are joined
+    /// into one entry, so a yank brings them all back.
+    fn kill(&mut self, text: String, edit: Edit) {
+        let joins = self.last_edit == Some(edit);
+        self.checkpoint(edit);
+
+        match self.kill_ring.last_mut() {
+            Some(last) if joins && edit == Edit::KillBackward => last.insert_str(0, &text),
+            Some(last) if joins => last.push_str(&text),
+            _ => self.push_kill(text),
+        }
+    }
+
+    fn push_kill(&mut self, text: String) {
+        self.kill_ring.push(text);
+        if self.kill_ring.len() > KILL_RING_SIZE {
+            self.kill_ring.remove(0);
+        }
+    }
+
+    /// Kills to the end of the line, or the line break when already there (Ctrl-K).
+    pub fn kill_to_end(&mut self) {
+        let start = self.cursor;
+        let end = if start.x < self.line_len(start.y) {
+            Pos::new(self.line_len(start.y), start.y)
+        } else {
+            match self.pos_right(start) {
+                Some(end) => end,
+                None => return,
+            }
+        };
+
+        let text = self.text_between(start, end);
+        self.kill(text, Edit::KillForward);
+        self.anchor = None;
+        self.remove_between(start, end);
+    }
+
+    /// Kills to the start of the line (Ctrl-U).
+    pub fn kill_to_start(&mut self) {
+        let end = self.cursor;
+        let start = Pos::new(0, end.y);
+        if start == end {
+            return;
+        }
+
+        let text = self.text_between(start, end);
+        self.kill(text, Edit::KillBackward);
+        self.anchor = None;
+        self.remove_between(start, end);
+    }
+
+    /// Kills the selection, or otherwise the word before the cursor (Ctrl-W).
+    pub fn kill_word_or_selection(&mut self) {
+        let (start, end) = match self.selection() {
+            Some(selection) => selection,
+            None => (self.word_left(self.cursor), self.cursor),
+        };
+        if start == end {
+            return;
+        }
+
+        let text = self.text_between(start, end);
+        self.kill(text, Edit::KillBackward);
+        self.anchor = None;
+        self.remove_between(start, end);
+    }
+
+    /// Adds the selection to the kill ring without removing it (Alt-W).
+    pub fn copy_selection(&mut self) {
+        if let Some((start, end)) = self.selection() {
+            let text = self.text_between(start, end);
+            // Nothing changed, so there's nothing to undo, and a kill after it isn't joined
+            self.push_kill(text);
+            self.last_edit = None;
+            self.anchor = None;
+        }
+    }
+
+    /// Inserts the newest kill (Ctrl-Y).
+    pub fn yank(&mut self) {
+        let index = match self.kill_ring.len().checked_sub(1) {
+            Some(index) => index,
+            None => return,
+        };
+        self.yank_at(index);
+    }
+
+    /// Replaces the text just yanked with the kill before it (Alt-Y).
+    pub fn yank_pop(&mut self) {
+        if let Some(Edit::Yank(index, start, end)) = self.last_edit {
+            self.remove_between(start, end);
+            let index = index.checked_sub(1).unwrap_or(self.kill_ring.len() - 1);
+            // No checkpoint, so undo goes back to before the first yank
+            self.insert_yank(index);
+        }
+    }
+
+    fn yank_at(&mut self, index: usize) {
+        self.checkpoint(Edit::Other);
+        self.take_selection();
+        self.insert_yank(index);
+    }
+
+    fn insert_yank(&mut self, index: usize) {
+        let text = self.kill_ring[index].clone();
+        let start = self.insert_wrapped(&text);
+        self.last_edit = Some(Edit::Yank(index, start, self.cursor));
+    }
+
+    pub fn undo(&mut self) {
+        if let Some(snapshot) = self.undo.pop() {
+            let current = self.restore(snapshot);
+            self.redo.push(current);
+        }
+    }
+
+    pub fn redo(&mut self) {
+        if let Some(snapshot) = self.redo.pop() {
+            let current = self.restore(snapshot);
+            self.undo.push(current);
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) -> Snapshot {
+        let current = Snapshot {
+            lines: std::mem::replace(&mut self.lines, snapshot.lines),
+            cursor: self.cursor,
+        };
+        self.cursor = snapshot.cursor;
+        self.anchor = None;
+        self.last_edit = None;
+        current
+    }
+
+    fn pos_left(&self, pos: Pos) -> Option<Pos> {
+        match pos {
+            Pos { x: 0, y: 0 } => None,
+            Pos { x: 0, y } => Some(Pos::new(self.line_len(y - 1), y - 1)),
+            Pos { x, y } => Some(Pos::new(x - 1, y)),
+        }
+    }
+
+    fn pos_right(&self, pos: Pos) -> Option<Pos> {
+        if pos.x < self.line_len(pos.y) {
+            Some(Pos::new(pos.x + 1, pos.y))
+        } else if pos.y + 1 < self.lines.len() {
+            Some(Pos::new(0, pos.y + 1))
+        } else {
+            None
+        }
+    }
+
+    /// The start of the word before `pos`, skipping any white-space before it, like Ctrl-W
+    /// in a shell.
+    fn word_left(&self, pos: Pos) -> Pos {
+        if pos.x == 0 {
+            return self.pos_left(pos).unwrap_or(pos);
+        }
+
+        let graphemes: Vec<&str> = Graphemes::new(&self.lines[pos.y]).take(pos.x).collect();
+        let is_space = |g: &str| g.chars().all(char::is_whitespace);
+
+        let mut x = pos.x;
+        while x > 0 && is_space(graphemes[x - 1]) {
+            x -= 1;
+        }
+        while x > 0 && !is_space(graphemes[x - 1]) {
+            x -= 1;
+        }
+        Pos::new(x, pos.y)
+    }
+
+    /// The end of the word after `pos`, skipping any white-space before it.
+    fn word_right(&self, pos: Pos) -> Pos {
+        if pos.x >= self.line_len(pos.y) {
+            return self.pos_right(pos).unwrap_or(pos);
+        }
+
+        let graphemes: Vec<&str> = Graphemes::new(&self.lines[pos.y]).collect();
+        let is_space = |g: &str| g.chars().all(char::is_whitespace);
+
+        let mut x = pos.x;
+        while x < graphemes.len() && is_space(graphemes[x]) {
+            x += 1;
+        }
+        while x < graphemes.len() && !is_space(graphemes[x]) {
+            x += 1;
+        }
+        Pos::new(x, pos.y)
+    }
+
+    /// Moves the cursor, extending the selection when `select` is set and clearing it
+    /// otherwise.
+    pub fn move_to(&mut self, motion: Motion, select: bool) {
+        if select {
+            self.anchor.get_or_insert(self.cursor);
+        } else {
+            self.anchor = None;
+        }
+        self.last_edit = None;
+
+        let pos = self.cursor;
+        let (new, keep_goal) = match motion {
+            Motion::Left => (self.pos_left(pos).unwrap_or(pos), false),
+            Motion::Right => (self.pos_right(pos).unwrap_or(pos), false),
+            Motion::WordLeft => (self.word_left(pos), false),
+            Motion::WordRight => (self.word_right(pos), false),
+            Motion::LineStart => (Pos::new(0, pos.y), false),
+            Motion::LineEnd => (Pos::new(self.line_len(pos.y), pos.y), false),
+            Motion::Up | Motion::Down => {
+                let goal = *self.goal_x.get_or_insert(pos.x);
+                let y = match motion {
+                    Motion::Up => pos.y.checked_sub(1),
+                    _ => Some(pos.y + 1).filter(|y| *y < self.lines.len()),
+                };
+                let new = match y {
+                    Some(y) => Pos::new(std::cmp::min(goal, self.line_len(y)), y),
+                    // Past the first or last line goes to its start or end
+                    None if motion == Motion::Up => Pos::new(0, 0),
+                    None => Pos::new(self.line_len(pos.y), pos.y),
+                };
+                (new, true)
+            }
+        };
+
+        if !keep_goal {
+            self.goal_x = None;
+        }
+        self.cursor = new;
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Motion {
+    Left,
+    Right,
+    WordLeft,
+    WordRight,
+    LineStart,
+    LineEnd,
+    Up,
+    Down,
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Motion, Pos, TextArea};
+
+    fn type_str(area: &mut TextArea, s: &str) {
+        for c in s.chars() {
+            match c {
+                '\n' => area.newline(),
+                c => area.insert_char(c),
+            }
+        }
+    }
+
+    #[test]
+    fn undo_redo() {
+        let mut area = TextArea::new("", 0);
+        type_str(&mut area, "one two\nthree");
+        area.delete_backward();
+        assert_eq!(area.text(), "one two\nthre");
+
+        area.undo();
+        assert_eq!(area.text(), "one two\nthree");
+        area.undo();
+        area.undo();
+        assert_eq!(area.text(), "one two");
+        area.undo();
+        assert_eq!(area.text(), "one");
+
+        area.redo();
+        area.redo();
+        assert_eq!(area.lines(), &["one two", ""]);
+        type_str(&mut area, "x");
+        area.redo();
+        assert_eq!(area.text(), "one two\nx");
+    }
+
+    #[test]
+    fn kill_and_yank() {
+        let mut area = TextArea::new("alpha beta gamma", 0);
+        area.kill_word_or_selection();
+        area.kill_word_or_selection();
+        assert_eq!(area.text(), "alpha");
+
+        // Consecutive kills are yanked together
+        area.yank();
+        assert_eq!(area.text(), "alpha beta gamma");
+
+        area.move_to(Motion::LineStart, false);
+        area.kill_to_end();
+        assert_eq!(area.text(), "");
+        area.yank();
+        area.yank_pop();
+        assert_eq!(area.text(), "beta gamma");
+        area.yank_pop();
+        assert_eq!(area.text(), "alpha beta gamma");
+    }
+
+    #[test]
+    fn kill_joins_lines() {
+        let mut area = TextArea::new("one\ntwo", 0);
+        area.move_to(Motion::LineStart, false);
+        area.move_to(Motion::Up, false);
+        area.kill_to_end();
+        area.kill_to_end();
+        assert_eq!(area.text(), "two");
+        area.yank();
+        assert_eq!(area.lines(), &["one", "two"]);
+    }
+
+    #[test]
+    fn selection() {
+        let mut area = TextArea::new("hello\nworld", 0);
+        area.move_to(Motion::LineStart, false);
+        area.move_to(Motion::Up, true);
+        area.move_to(Motion::LineEnd, true);
+        assert_eq!(area.selection(), Some((Pos::new(5, 0), Pos::new(0, 1))));
+        assert_eq!(area.selected_columns(0), Some((5, 6)));
+
+        area.move_to(Motion::LineStart, true);
+        area.insert_char('X');
+        assert_eq!(area.text(), "Xworld");
+
+        area.undo();
+        assert_eq!(area.text(), "hello\nworld");
+
+        // Copying doesn't count as an edit, so undo goes back to before the typing
+        let mut area = TextArea::new("", 0);
+        type_str(&mut area, "hello");
+        area.move_to(Motion::LineStart, true);
+        area.copy_selection();
+        area.undo();
+        assert_eq!(area.text(), "");
+        area.yank();
+        assert_eq!(area.text(), "hello");
+    }
+
+    #[test]
+    fn yank_wraps() {
+        let mut area = TextArea::new("zz", 0);
+        area.kill_to_start();
+        area.insert_str("aaaa bbbb cccc");
+        area.kill_to_start();
+        area.wrap = 10;
+
+        area.insert_str("dd ee ff");
+        area.yank();
+        assert_eq!(area.lines(), &["dd ee", "ffaaaa", "bbbb cccc"]);
+
+        // The yank moved to the next line when the line it started on was wrapped
+        area.yank_pop();
+        assert_eq!(area.lines(), &["dd ee", "ffzz"]);
+    }
+
+    #[test]
+    fn paste_wraps() {
+        let mut area = TextArea::new("", 10);
+        area.insert_str("aaa bbb ccc ddd\n\nx");
+        assert_eq!(area.lines(), &["aaa bbb", "ccc ddd", "", "x"]);
+        assert_eq!(area.cursor(), Pos::new(1, 3));
+
+        area.undo();
+        assert_eq!(area.text(), "");
+    }
+}
@@ -1,16 +1,22 @@
+use super::fuzzy::fuzzy_match;
 use crate::color::reset_display;
-use crate::Config;
+use crate::string;
 use crate::TermBuffer;
+use crate::{CommitType, Config};
 use crossterm::{
+    self as ct,
     event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
     style::{style, Color},
 };
+use std::cmp::Reverse;
 
 #[derive(Debug)]
 pub struct TypePrompt<'a> {
     config: &'a Config,
     input: String,
     focused_index: u16,
+    /// The index of the first option shown, when they don't all fit on screen.
+    scroll_offset: usize,
 }
 
 pub enum TypePromptResult {
@@ -19,40 +25,105 @@ pub enum TypePromptResult {
     Terminate,
 }
 
+/// A type matching the input, with the chars of its name to highlight.
+#[derive(Debug)]
+struct TypeMatch<'a> {
+    ty: &'a CommitType,
+    score: i32,
+    /// Empty when the input matched an alias rather than the name.
+    indices: Vec<usize>,
+}
+
 impl<'a> TypePrompt<'a> {
     pub fn new(config: &'a Config) -> Self {
         TypePrompt {
             config,
             input: Default::default(),
             focused_index: 0,
+            scroll_offset: 0,
+        }
+    }
+
+    /// Focuses `ty`, e.g. the type chosen before, so Enter keeps it. A custom type is typed
+    /// out, so it's offered again.
+    pub fn prefill(mut self, ty: &str) -> Self {
+        match self
+            .config
+            .types
+            .iter()
+            .position(|listed| listed.name == ty)
+        {
+            Some(index) => self.focused_index = index as u16,
+            None => {
+                self.input = ty.to_string();
+                self.focused_index = self.filter_types().len() as u16;
+            }
         }
+        self
     }
 
-    /// Attempts to find the item at `self.selected_index`. If greater than
-    /// the number of items, then the last item, or finally falling back to "misc" which
-    /// doesn't normally appear in commitlint.
-    fn get_at_selected_index(&self) -> &str {
+    /// Attempts to find the item at `self.selected_index`. If greater than the number of
+    /// items, then the last item, which is the custom type entry when it's shown.
+    fn get_at_selected_index(&self) -> Option<String> {
         let options = self.filter_types();
-        options
-            .get(self.focused_index as usize)
-            .or_else(|| options.last())
-            .copied()
-            .unwrap_or("misc")
+        let custom = self.custom_type().ok().flatten();
+        let total = options.len() + custom.iter().len();
+
+        match std::cmp::min(self.focused_index as usize, total.checked_sub(1)?) {
+            i if i < options.len() => Some(options[i].ty.name.clone()),
+            _ => custom.map(String::from),
+        }
     }
 
-    fn filter_types(&self) -> Vec<&str> {
-        self.config
+    /// The input, if it should be offered as a custom type: it isn't empty and isn't already
+    /// a listed type. An error explains why a custom type can't be used.
+    fn custom_type(&self) -> Result<Option<&str>, String> {
+        if self.input.is_empty() || self.config.type_names().any(|name| name == self.input) {
+            return Ok(None);
+        }
+
+        self.config.check_custom_type(&self.input)?;
+        Ok(Some(&self.input))
+    }
+
+    /// The types whose name or an alias fuzzy matches the input, best match first. Equal
+    /// matches keep their configured order.
+    fn filter_types(&self) -> Vec<TypeMatch<'a>> {
+        let mut matches: Vec<TypeMatch<'a>> = self
+            .config
             .types
             .iter()
-            .map(|x| x.as_str())
-            .filter(|item| {
-                if self.input.is_empty() {
-                    true
-                } else {
-                    item.starts_with(&self.input)
-                }
+            .filter_map(|ty| {
+                let name = fuzzy_match(&self.input, &ty.name).map(|m| (m.score, m.indices));
+                let alias = ty
+                    .aliases
+                    .iter()
+                    .filter_map(|alias| fuzzy_match(&self.input, alias))
+                    .map(|m| (m.score, vec![]))
+                    .max_by_key(|(score, _)| *score);
+
+                let (score, indices) = match (name, alias) {
+                    (Some(name), Some(alias)) if alias.0 > name.0 => alias,
+                    (Some(name), _) => name,
+                    (None, alias) => alias?,
+                };
+
+                Some(TypeMatch { ty, score, indices })
             })
-            .collect()
+            .collect();
+
+        matches.sort_by_key(|m| Reverse(m.score));
+        matches
+    }
+
+    /// Scrolls so the focused option is one of the `rows` shown.
+    fn scroll_to_focused(&mut self, rows: usize) {
+        let focused = self.focused_index as usize;
+        if focused < self.scroll_offset {
+            self.scroll_offset = focused;
+        } else if focused >= self.scroll_offset + rows {
+            self.scroll_offset = focused + 1 - rows;
+        }
     }
 
     pub fn run(mut self) -> TypePromptResult {
@@ -85,13 +156,17 @@ impl<'a> TypePrompt<'a> {
                     return TypePromptResult::Terminate;
                 }
                 Some((KeyCode::Enter, false, false, false)) => {
-                    return TypePromptResult::Type(self.get_at_selected_index().to_string());
+                    if let Some(ty) = self.get_at_selected_index() {
+                        return TypePromptResult::Type(ty);
+                    }
                 }
                 Some((KeyCode::Char(c), false, _, false)) => {
                     self.input.push(c.to_ascii_lowercase());
+                    self.focused_index = 0;
                 }
                 Some((KeyCode::Backspace, false, _, false)) => {
                     self.input.pop();
+                    self.focused_index = 0;
                 }
                 Some((KeyCode::Esc, false, _, false)) => {
                     return TypePromptResult::Escape;
@@ -100,7 +175,8 @@ impl<'a> TypePrompt<'a> {
                     self.focused_index = self.focused_index.saturating_sub(1);
                 }
                 Some((KeyCode::Down, false, _, false)) => {
-                    let total = self.config.types.len() as u16;
+                    let custom = self.custom_type().ok().flatten();
+                    let total = (self.filter_types().len() + custom.iter().len()) as u16;
 
                     self.focused_index += 1;
                     if self.focused_index >= total {
@@ -112,8 +188,13 @@ impl<'a> TypePrompt<'a> {
             };
 
             let types = self.filter_types();
-            if types.len() == 1 {
-                return TypePromptResult::Type(types[0].to_string());
+            // A prefilled type is shown first, rather than submitted straight away
+            if self.config.type_auto_submit
+                && event.is_some()
+                && !self.input.is_empty()
+                && types.len() == 1
+            {
+                return TypePromptResult::Type(types[0].ty.name.clone());
             }
 
             let mut header = figlet.create_vec();
@@ -130,7 +211,7 @@ impl<'a> TypePrompt<'a> {
             let after_prompt_x = {
                 let prompt_pre = "Choose a type: ";
                 let prompt_post = &self.input;
-                let underscores = "_".repeat(6_usize.saturating_sub(self.input.len()));
+                let underscores = "_".repeat(6_usize.saturating_sub(string::width(&self.input)));
                 buffer.push_line("");
                 buffer.push_line(format!(
                     "{}{}{}{}",
@@ -139,24 +220,112 @@ impl<'a> TypePrompt<'a> {
                     style(underscores).with(crate::color::theme_user_input()),
                     reset_display()
                 ));
-                let x = prompt_pre.len() + prompt_post.len();
+                let x = prompt_pre.len() + string::width(prompt_post);
                 x as u16
             };
 
             let focused_color = Color::Blue;
             let default_color = Color::Reset;
+            let name_width = types
+                .iter()
+                .map(|m| string::width(&m.ty.name))
+                .max()
+                .unwrap_or(0);
+            // Why a custom type can't be used is only worth showing when nothing matches
+            let custom = match self.custom_type() {
+                Err(_) if !types.is_empty() => Ok(None),
+                custom => custom.map(|ty| ty.map(String::from)),
+            };
+            let custom_rows = match custom {
+                Ok(None) => 0,
+                _ => 1,
+            };
 
-            for (i, ty) in types.into_iter().enumerate() {
-                let line_content = if i as u16 == self.focused_index {
-                    style(["*", " ", ty].concat()).with(focused_color)
+            // Leave a row for the cursor, and one for the scroll position if it's needed
+            let (term_width, term_height) = ct::terminal::size().unwrap_or((80, 24));
+            let mut rows = (term_height as usize).saturating_sub(buffer.lines() as usize + 1);
+            rows = rows.saturating_sub(custom_rows);
+            let overflows = types.len() > rows;
+            if overflows {
+                rows = std::cmp::max(rows.saturating_sub(1), 1);
+            }
+            if (self.focused_index as usize) < types.len() {
+                self.scroll_to_focused(rows);
+            }
+
+            for (i, m) in types.iter().enumerate().skip(self.scroll_offset).take(rows) {
+                let (marker, color) = if i as u16 == self.focused_index {
+                    ("*", focused_color)
                 } else {
-                    style(["-", " ", ty].concat()).with(default_color)
+                    ("-", default_color)
                 };
+                let name_color = m.ty.color.unwrap_or(color);
+
+                let mut line = format!("{} ", style(marker).with(color));
+                for (j, c) in m.ty.name.chars().enumerate() {
+                    if m.indices.contains(&j) {
+                        line.push_str(&style(c).with(Color::Green).to_string());
+                    } else {
+                        line.push_str(&style(c).with(name_color).to_string());
+                    }
+                }
+                line.push_str(&" ".repeat(name_width - string::width(&m.ty.name)));
+                let mut used = 2 + name_width;
+
+                if let Some(emoji) = &m.ty.emoji {
+                    line.push_str(&format!(" {}", emoji));
+                    used += 1 + string::width(emoji);
+                }
+                if let Some(description) = &m.ty.description {
+                    // Kept short of the last column, so the terminal never wraps the row
+                    let available = (term_width as usize).saturating_sub(used + 3);
+                    let description = string::truncate_with_ellipsis(description, available);
+                    line.push_str(&format!("  {}", style(description).with(Color::DarkGrey)));
+                }
+                line.push_str(&reset_display().to_string());
 
-                let line = format!("{}{}", line_content, reset_display());
                 buffer.push_line(line);
             }
 
+            if overflows {
+                let last = std::cmp::min(self.scroll_offset + rows, types.len());
+                buffer.push_line(format!(
+                    "{}",
+                    style(format!(
+                        "  {}-{} of {}",
+                        self.scroll_offset + 1,
+                        last,
+                        types.len()
+                    ))
+                    .with(Color::DarkGrey)
+                ));
+            }
+
+            // The custom type entry stays below the list, rather than scrolling with it
+            match custom {
+                Ok(Some(ty)) => {
+                    let (marker, color) = if self.focused_index as usize >= types.len() {
+                        ("*", focused_color)
+                    } else {
+                        ("+", default_color)
+                    };
+                    buffer.push_line(format!(
+                        "{} {}{}",
+                        style(marker).with(color),
+                        style(format!("use '{}' as a custom type", ty)).with(color),
+                        reset_display()
+                    ));
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    buffer.push_line(format!(
+                        "{}{}",
+                        style(format!("  {}", err)).with(Color::DarkGrey),
+                        reset_display()
+                    ));
+                }
+            }
+
             buffer.set_next_cursor((after_prompt_x, y_offset));
             buffer.render_frame();
             buffer.flush();
@@ -0,0 +1,215 @@
+//! Ranking scopes from history, and inferring them from paths, for the scope prompt.
+
+use crate::filter::glob_match;
+use crate::LogItem;
+use serde::Deserialize;
+use std::convert::TryFrom;
+
+/// How many commits back a use of a scope counts for half as much.
+const HALF_LIFE: f64 = 50.0;
+
+/// Ranks the scopes used in `items` (newest first) by how often and how recently they were
+/// used. Scopes are lower-cased, since that's what the prompt accepts.
+///
+/// If `configured` isn't empty, only those scopes are suggested, and any that haven't been
+/// used come last in their configured order.
+pub fn suggest<'a>(
+    items: impl IntoIterator<Item = &'a LogItem>,
+    configured: &[String],
+) -> Vec<String> {
+    let mut scores: Vec<(String, f64)> = configured
+        .iter()
+        .map(|scope| (scope.to_lowercase(), 0.0))
+        .collect();
+
+    for (i, item) in items.into_iter().enumerate() {
+        let scope = match item.as_conventional().and_then(|conv| conv.scope) {
+            Some(scope) => scope,
+            None => continue,
+        };
+        let weight = 0.5_f64.powf(i as f64 / HALF_LIFE);
+
+        for scope in scope.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let scope = scope.to_lowercase();
+
+            match scores.iter_mut().find(|(known, _)| *known == scope) {
+                Some((_, score)) => *score += weight,
+                None if configured.is_empty() => scores.push((scope, weight)),
+                None => {}
+            }
+        }
+    }
+
+    // The sort is stable, so equal scores keep their configured or most recent first order
+    scores.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+    scores.into_iter().map(|(scope, _)| scope).collect()
+}
+
+/// A `scope_paths` entry like `crates/server/** => server`. In the pattern, `*` matches within
+/// a directory and `**` matches any number of directories. In the scope, `$1` is replaced by
+/// the part of the path matched by the first wildcard, so `packages/*/** => $1` gives the
+/// directory under `packages/`.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct ScopePath {
+    pattern: String,
+    scope: String,
+}
+
+impl TryFrom<String> for ScopePath {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        let mut parts = s.splitn(2, "=>").map(str::trim);
+
+        match (parts.next(), parts.next()) {
+            (Some(pattern), Some(scope)) if !pattern.is_empty() && !scope.is_empty() => {
+                Ok(ScopePath {
+                    pattern: pattern.trim_matches('/').to_string(),
+                    scope: scope.to_string(),
+                })
+            }
+            _ => Err(format!(
+                "expected a scope path like \"src/api/** => api\", found \"{}\"",
+                s
+            )),
+        }
+    }
+}
+
+/// Matches path segments, collecting the segments matched by wildcards into `captures`.
+fn match_segments<'a>(pattern: &[&str], path: &[&'a str], captures: &mut Vec<&'a str>) -> bool {
+    let (first, rest) = match pattern.split_first() {
+        Some(split) => split,
+        None => return path.is_empty(),
+    };
+
+    if *first == "**" {
+        return (0..=path.len()).any(|skip| {
+            let len = captures.len();
+            match_segments(rest, &path[skip..], captures) || {
+                captures.truncate(len);
+                false
+            }
+        });
+    }
+
+    match path.split_first() {
+        Some((segment, path_rest)) if glob_match(first, segment) => {
+            let is_wildcard = first.contains(&['*', '?'][..]);
+            if is_wildcard {
+                captures.push(segment);
+            }
+
+            match_segments(rest, path_rest, captures) || {
+                if is_wildcard {
+                    captures.pop();
+                }
+                false
+            }
+        }
+        _ => false,
+    }
+}
+
+impl ScopePath {
+    /// The scope for `path`, relative to the repository root, if it matches.
+    pub fn scope_for(&self, path: &str) -> Option<String> {
+        let pattern: Vec<&str> = self.pattern.split('/').collect();
+        let path: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+        let mut captures = vec![];
+        if !match_segments(&pattern, &path, &mut captures) {
+            return None;
+        }
+
+        let mut scope = self.scope.clone();
+        for (i, capture) in captures.iter().enumerate().rev() {
+            scope = scope.replace(&format!("${}", i + 1), capture);
+        }
+
+        Some(scope.to_lowercase()).filter(|scope| !scope.is_empty())
+    }
+}
+
+/// The scopes inferred from `paths`, most common first. Each path uses the first mapping that
+/// matches it, and a rename like `old -> new` uses the new path.
+pub fn infer<'a>(mappings: &[ScopePath], paths: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut counts: Vec<(String, usize)> = vec![];
+
+    for path in paths {
+        let path = path.rsplit(" -> ").next().unwrap_or(path);
+        let scope = match mappings.iter().find_map(|mapping| mapping.scope_for(path)) {
+            Some(scope) => scope,
+            None => continue,
+        };
+
+        match counts.iter_mut().find(|(known, _)| *known == scope) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((scope, 1)),
+        }
+    }
+
+    counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    counts.into_iter().map(|(scope, _)| scope).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{infer, suggest, ScopePath};
+    use crate::LogItem;
+    use std::convert::TryFrom;
+
+    fn items(messages: &[&str]) -> Vec<LogItem> {
+        messages.iter().copied().map(LogItem::test).collect()
+    }
+
+    #[test]
+    fn ranks_by_frequency_and_recency() {
+        let items = items(&[
+            "feat(ui): a",
+            "fix(Auth): b",
+            "feat(api,auth): c",
+            "chore: d",
+            "fix(api): e",
+        ]);
+        assert_eq!(suggest(&items, &[]), vec!["auth", "api", "ui"]);
+    }
+
+    #[test]
+    fn only_configured() {
+        let items = items(&["feat(ui): a", "fix(api): b", "fix(db): c"]);
+        let configured = vec!["db".to_string(), "docs".to_string(), "api".to_string()];
+        assert_eq!(suggest(&items, &configured), vec!["api", "db", "docs"]);
+    }
+
+    #[test]
+    fn infers_from_paths() {
+        let mappings: Vec<ScopePath> = vec!["crates/server/** => server", "packages/*/** => $1"]
+            .into_iter()
+            .map(|s| ScopePath::try_from(s.to_string()).unwrap())
+            .collect();
+
+        assert_eq!(
+            mappings[0].scope_for("crates/server/src/main.rs"),
+            Some("server".into())
+        );
+        assert_eq!(mappings[0].scope_for("crates/server2/src/main.rs"), None);
+        assert_eq!(
+            mappings[1].scope_for("packages/UI/index.js"),
+            Some("ui".into())
+        );
+
+        let paths = vec![
+            "packages/web/a.js",
+            "crates/server/lib.rs",
+            "crates/server/Cargo.toml",
+            "README.md",
+            "old.rs -> packages/web/b.js",
+            "packages/web/c.js",
+        ];
+        assert_eq!(infer(&mappings, paths), vec!["web", "server"]);
+
+        assert!(ScopePath::try_from("src/**".to_string()).is_err());
+    }
+}
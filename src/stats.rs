@@ -0,0 +1,187 @@
+//! Commit counts by type, scope, author, and week, for `glint stats`.
+
+use crate::changelog::format_date;
+use crate::LogItem;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+const DAY_SECS: i64 = 86_400;
+
+/// Something and how many commits it had, or how many times a file was changed.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct Count {
+    pub name: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct ScopeFiles {
+    pub scope: String,
+    pub files: Vec<Count>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Stats {
+    pub total: usize,
+    pub non_conventional: usize,
+    /// `non_conventional` as a fraction of `total`, from 0 to 1.
+    pub non_conventional_share: f64,
+    pub by_type: Vec<Count>,
+    pub by_scope: Vec<Count>,
+    pub by_author: Vec<Count>,
+    /// Keyed by the date of the Monday starting each week, oldest first.
+    pub by_week: Vec<Count>,
+    /// The most changed files for each scope.
+    pub files_by_scope: Vec<ScopeFiles>,
+}
+
+/// Counts commits one at a time, so a long history doesn't have to be kept in memory.
+#[derive(Debug, Clone, Default)]
+pub struct StatsBuilder {
+    total: usize,
+    non_conventional: usize,
+    types: BTreeMap<String, usize>,
+    scopes: BTreeMap<String, usize>,
+    authors: BTreeMap<String, usize>,
+    weeks: BTreeMap<i64, usize>,
+    scope_files: BTreeMap<String, BTreeMap<String, usize>>,
+}
+
+/// The first day (Monday) of the week containing `epoch_secs`, in days since the epoch.
+fn week_start(epoch_secs: i64) -> i64 {
+    let days = epoch_secs.div_euclid(DAY_SECS);
+    // The epoch was a Thursday
+    days - (days + 3).rem_euclid(7)
+}
+
+/// Sorted by count, most first, then by name (the sort is stable).
+fn sorted(counts: BTreeMap<String, usize>) -> Vec<Count> {
+    let mut counts: Vec<Count> = counts
+        .into_iter()
+        .map(|(name, count)| Count { name, count })
+        .collect();
+    counts.sort_by_key(|count| Reverse(count.count));
+    counts
+}
+
+impl StatsBuilder {
+    pub fn add(&mut self, item: &LogItem) {
+        self.total += 1;
+
+        let author = if item.author.name.is_empty() {
+            &item.author.email
+        } else {
+            &item.author.name
+        };
+        *self.authors.entry(author.clone()).or_default() += 1;
+
+        let week = week_start(item.committer.local_epoch_secs());
+        *self.weeks.entry(week).or_default() += 1;
+
+        let conv = match item.as_conventional() {
+            Some(conv) => conv,
+            None => {
+                self.non_conventional += 1;
+                return;
+            }
+        };
+
+        *self.types.entry(conv.ty.to_string()).or_default() += 1;
+
+        let scopes = conv.scope.into_iter().flat_map(|scope| scope.split(','));
+        for scope in scopes.map(str::trim).filter(|scope| !scope.is_empty()) {
+            *self.scopes.entry(scope.to_string()).or_default() += 1;
+
+            let files = self.scope_files.entry(scope.to_string()).or_default();
+            for file in &item.files {
+                *files.entry(file.path.clone()).or_default() += 1;
+            }
+        }
+    }
+
+    /// Finishes the report, keeping the `top_files` most changed files per scope.
+    pub fn build(self, top_files: usize) -> Stats {
+        let non_conventional_share = if self.total == 0 {
+            0.0
+        } else {
+            self.non_conventional as f64 / self.total as f64
+        };
+
+        Stats {
+            total: self.total,
+            non_conventional: self.non_conventional,
+            non_conventional_share,
+            by_type: sorted(self.types),
+            by_scope: sorted(self.scopes),
+            by_author: sorted(self.authors),
+            by_week: self
+                .weeks
+                .into_iter()
+                .map(|(week, count)| Count {
+                    name: format_date(week * DAY_SECS),
+                    count,
+                })
+                .collect(),
+            files_by_scope: self
+                .scope_files
+                .into_iter()
+                .filter(|(_, files)| !files.is_empty())
+                .map(|(scope, files)| {
+                    let mut files = sorted(files);
+                    files.truncate(top_files);
+                    ScopeFiles { scope, files }
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Count, StatsBuilder};
+    use crate::LogItem;
+
+    fn count(name: &str, count: usize) -> Count {
+        Count {
+            name: name.into(),
+            count,
+        }
+    }
+
+    #[test]
+    fn counts() {
+        let mut builder = StatsBuilder::default();
+        // 2020-01-06 was a Monday
+        builder.add(
+            &LogItem::test("feat(api): a")
+                .author("Ann", 1_578_268_800)
+                .files(&["api.rs"]),
+        );
+        builder.add(
+            &LogItem::test("fix(api,ui): b")
+                .author("Bob", 1_578_700_000)
+                .files(&["api.rs", "ui.rs"]),
+        );
+        builder.add(
+            &LogItem::test("feat: c")
+                .author("Ann", 1_578_873_600)
+                .files(&["lib.rs"]),
+        );
+        builder.add(&LogItem::test("wip").author("Ann", 1_578_873_600).files(&[]));
+        let stats = builder.build(1);
+
+        assert_eq!(stats.total, 4);
+        assert_eq!(stats.non_conventional, 1);
+        assert_eq!(stats.by_type, vec![count("feat", 2), count("fix", 1)]);
+        assert_eq!(stats.by_scope, vec![count("api", 2), count("ui", 1)]);
+        assert_eq!(stats.by_author, vec![count("Ann", 3), count("Bob", 1)]);
+        assert_eq!(
+            stats.by_week,
+            vec![count("2020-01-06", 2), count("2020-01-13", 2)]
+        );
+        assert_eq!(stats.files_by_scope[0].files, vec![count("api.rs", 2)]);
+        // Ties are sorted by name
+        assert_eq!(stats.files_by_scope[1].files, vec![count("api.rs", 1)]);
+    }
+}
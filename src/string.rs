@@ -1,10 +1,31 @@
+use std::borrow::Cow;
 use std::ops::Range;
 use unic_segment::{Graphemes, WordBounds};
+use unicode_width::UnicodeWidthChar;
 
 pub fn len(s: &str) -> usize {
     Graphemes::new(s).count()
 }
 
+/// The number of terminal columns a grapheme takes up. Wide characters like CJK take two, as
+/// do emoji presentation sequences, and control characters take none.
+pub fn grapheme_width(grapheme: &str) -> usize {
+    if grapheme.contains('\u{FE0F}') {
+        return 2;
+    }
+
+    grapheme
+        .chars()
+        .next()
+        .and_then(UnicodeWidthChar::width)
+        .unwrap_or(0)
+}
+
+/// The number of terminal columns `s` takes up.
+pub fn width(s: &str) -> usize {
+    Graphemes::new(s).map(grapheme_width).sum()
+}
+
 pub fn to_byte_offset(s: &'_ str, grapheme_offset: usize) -> usize {
     let mut byte_offset = 0;
 
@@ -51,6 +72,66 @@ pub fn split_at(s: &str, grapheme_offset: usize) -> (&str, &str) {
     (&s[0..byte_offset], &s[byte_offset..])
 }
 
+/// Splits `s` so the first part takes up at most `columns` terminal columns. A wide grapheme
+/// that would only half fit goes in the second part.
+pub fn split_at_width(s: &str, columns: usize) -> (&str, &str) {
+    let mut byte_offset = 0;
+    let mut width = 0;
+
+    for grapheme in Graphemes::new(s) {
+        width += grapheme_width(grapheme);
+        if width > columns {
+            break;
+        }
+        byte_offset += grapheme.len();
+    }
+
+    (&s[0..byte_offset], &s[byte_offset..])
+}
+
+/// Shortens `s` to at most `columns` terminal columns, ending it with "…" if anything was
+/// cut off.
+pub fn truncate_with_ellipsis(s: &str, columns: usize) -> Cow<'_, str> {
+    if width(s) <= columns {
+        return Cow::Borrowed(s);
+    }
+
+    match columns.checked_sub(1) {
+        Some(columns) => Cow::Owned(format!("{}…", split_at_width(s, columns).0)),
+        None => Cow::Borrowed(""),
+    }
+}
+
+/// Wraps `s` into lines of at most `columns` terminal columns, breaking at spaces where it
+/// can and within words that don't fit on a line of their own. The spaces at a break are
+/// dropped.
+pub fn wrap(s: &str, columns: usize) -> Vec<&str> {
+    let mut lines = vec![];
+    let mut rest = s;
+
+    while width(rest) > columns {
+        let (head, _) = split_at_width(rest, columns);
+
+        // Break at the last space that fits, which may be the one right after the line
+        let fits =
+            &rest[..head.len() + rest[head.len()..].chars().next().map_or(0, char::len_utf8)];
+        let (line, next) = match fits.rfind(' ') {
+            Some(i) if !rest[..i].trim().is_empty() => (&rest[..i], &rest[i..]),
+            // A word wider than the line, or a grapheme wider than `columns`
+            _ if head.is_empty() => rest.split_at(to_byte_offset(rest, 1)),
+            _ => (head, &rest[head.len()..]),
+        };
+
+        lines.push(line.trim_end());
+        rest = next.trim_start_matches(' ');
+    }
+
+    if !rest.is_empty() || lines.is_empty() {
+        lines.push(rest);
+    }
+    lines
+}
+
 pub fn prev_word_grapheme(s: &str, current_offset: usize) -> usize {
     let mut grapheme_offset = 0;
 
@@ -81,3 +162,41 @@ pub fn next_word_grapheme(s: &str, current_offset: usize) -> usize {
 
     grapheme_offset
 }
+
+#[cfg(test)]
+mod test {
+    use super::{split_at_width, truncate_with_ellipsis, width, wrap};
+
+    #[test]
+    fn widths() {
+        assert_eq!(width("abc"), 3);
+        assert_eq!(width("日本語"), 6);
+        assert_eq!(width("e\u{301}"), 1);
+        assert_eq!(width("❤\u{FE0F}"), 2);
+        assert_eq!(width("👨\u{200D}👩\u{200D}👧"), 2);
+
+        assert_eq!(split_at_width("日本語", 3), ("日", "本語"));
+        assert_eq!(split_at_width("ab", 5), ("ab", ""));
+    }
+
+    #[test]
+    fn truncates() {
+        assert_eq!(truncate_with_ellipsis("short", 5), "short");
+        assert_eq!(truncate_with_ellipsis("longer", 5), "long…");
+        assert_eq!(truncate_with_ellipsis("日本語", 4), "日…");
+        assert_eq!(truncate_with_ellipsis("abc", 0), "");
+    }
+
+    #[test]
+    fn wraps() {
+        assert_eq!(wrap("one two three", 7), vec!["one two", "three"]);
+        assert_eq!(wrap("one two three", 8), vec!["one two", "three"]);
+        assert_eq!(wrap("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+        assert_eq!(
+            wrap("日本語のテキスト", 5),
+            vec!["日本", "語の", "テキ", "スト"]
+        );
+        assert_eq!(wrap("", 5), vec![""]);
+        assert_eq!(wrap("日", 1), vec!["日"]);
+    }
+}
@@ -0,0 +1,267 @@
+//! Semantic versions, and choosing the next one from conventional commits.
+
+use crate::{Conventional, LogItem};
+use std::cmp::Ordering;
+use std::fmt;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    /// The pre-release part without the leading `-`, e.g. "rc.1".
+    pub pre: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Bump {
+    Patch,
+    Minor,
+    Major,
+}
+
+impl Version {
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Version {
+            major,
+            minor,
+            patch,
+            pre: None,
+        }
+    }
+
+    /// Parses `1.2.3` or `1.2.3-rc.1`, with an optional `v` prefix. Build metadata is ignored.
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.strip_prefix('v').unwrap_or(s);
+        let s = s.split('+').next().unwrap_or(s);
+
+        let (core, pre) = match s.find('-') {
+            Some(i) => (&s[..i], Some(&s[i + 1..])),
+            None => (s, None),
+        };
+
+        let mut parts = core.split('.').map(|part| part.parse::<u64>().ok());
+        let version = Version {
+            major: parts.next()??,
+            minor: parts.next()??,
+            patch: parts.next()??,
+            pre: match pre {
+                Some("") => return None,
+                pre => pre.map(String::from),
+            },
+        };
+
+        match parts.next() {
+            Some(_) => None,
+            None => Some(version),
+        }
+    }
+
+    /// Applies `bump`. With `pre_major`, versions below 1.0.0 treat breaking changes as minor
+    /// and features as patch, so a 0.x project doesn't jump to 1.0.0 by accident.
+    pub fn bump(&self, bump: Bump, pre_major: bool) -> Self {
+        let bump = match bump {
+            Bump::Major if pre_major && self.major == 0 => Bump::Minor,
+            Bump::Minor if pre_major && self.major == 0 => Bump::Patch,
+            bump => bump,
+        };
+
+        // A pre-release of the version we'd bump to just drops its pre-release part,
+        // e.g. 2.0.0-rc.1 with a breaking change becomes 2.0.0.
+        if self.pre.is_some() {
+            let already_bumped = match bump {
+                Bump::Major => self.minor == 0 && self.patch == 0,
+                Bump::Minor => self.patch == 0,
+                Bump::Patch => true,
+            };
+
+            if already_bumped {
+                return Version::new(self.major, self.minor, self.patch);
+            }
+        }
+
+        match bump {
+            Bump::Major => Version::new(self.major + 1, 0, 0),
+            Bump::Minor => Version::new(self.major, self.minor + 1, 0),
+            Bump::Patch => Version::new(self.major, self.minor, self.patch + 1),
+        }
+    }
+
+    /// Turns the release `self` into a pre-release like `1.3.0-rc.0`, continuing the numbering
+    /// if `previous` is already a pre-release of the same version and identifier.
+    pub fn with_prerelease(&self, identifier: &str, previous: &Version) -> Self {
+        let same_release = (self.major, self.minor, self.patch)
+            == (previous.major, previous.minor, previous.patch);
+
+        let number = previous
+            .pre
+            .as_ref()
+            .filter(|_| same_release)
+            .and_then(|pre| {
+                pre.strip_prefix(identifier)?
+                    .strip_prefix('.')?
+                    .parse::<u64>()
+                    .ok()
+            })
+            .map(|n| n + 1)
+            .unwrap_or(0);
+
+        Version {
+            pre: Some(format!("{}.{}", identifier, number)),
+            ..self.clone()
+        }
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            .then_with(|| match (&self.pre, &other.pre) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(a), Some(b)) => compare_prerelease(a, b),
+            })
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Compares dot-separated identifiers, numerically where both are numbers.
+fn compare_prerelease(a: &str, b: &str) -> Ordering {
+    let mut a_parts = a.split('.');
+    let mut b_parts = b.split('.');
+
+    loop {
+        let ordering = match (a_parts.next(), b_parts.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a), Some(b)) => match (a.parse::<u64>(), b.parse::<u64>()) {
+                (Ok(a), Ok(b)) => a.cmp(&b),
+                (Ok(_), Err(_)) => Ordering::Less,
+                (Err(_), Ok(_)) => Ordering::Greater,
+                (Err(_), Err(_)) => a.cmp(b),
+            },
+        };
+
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if let Some(ref pre) = self.pre {
+            write!(f, "-{}", pre)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Bump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Bump::Major => write!(f, "major"),
+            Bump::Minor => write!(f, "minor"),
+            Bump::Patch => write!(f, "patch"),
+        }
+    }
+}
+
+/// The bump required by a commit: major for breaking changes, minor for `feat`,
+/// patch for `fix` and `perf`.
+pub fn bump_for(item: &LogItem) -> Option<Bump> {
+    let conv = Conventional::parse(&item.message)?;
+
+    if conv.breaking {
+        return Some(Bump::Major);
+    }
+
+    match conv.ty {
+        "feat" => Some(Bump::Minor),
+        "fix" | "perf" => Some(Bump::Patch),
+        _ => None,
+    }
+}
+
+/// The largest bump required by any of `items`, or `None` if nothing needs a release.
+pub fn next_bump(items: &[LogItem]) -> Option<Bump> {
+    items.iter().filter_map(bump_for).max()
+}
+
+/// The latest release among `tags`, with its version. Tags that aren't semantic versions,
+/// like `nightly`, are skipped.
+pub fn latest_release<'a>(tags: impl IntoIterator<Item = &'a str>) -> Option<(&'a str, Version)> {
+    tags.into_iter()
+        .filter_map(|tag| Version::parse(tag).map(|version| (tag, version)))
+        .max_by(|(_, a), (_, b)| a.cmp(b))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{latest_release, Bump, Version};
+
+    fn v(s: &str) -> Version {
+        Version::parse(s).unwrap()
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(v("v1.2.3"), Version::new(1, 2, 3));
+        assert_eq!(v("1.2.3-rc.1+build.5").pre, Some("rc.1".into()));
+        assert_eq!(Version::parse("1.2"), None);
+        assert_eq!(Version::parse("1.2.3.4"), None);
+        assert_eq!(Version::parse("release-1"), None);
+    }
+
+    #[test]
+    fn ordering() {
+        assert!(v("1.0.0-alpha") < v("1.0.0-alpha.1"));
+        assert!(v("1.0.0-alpha.2") < v("1.0.0-alpha.10"));
+        assert!(v("1.0.0-rc.1") < v("1.0.0"));
+        assert!(v("1.0.0") < v("1.0.1"));
+    }
+
+    #[test]
+    fn bump() {
+        assert_eq!(v("1.2.3").bump(Bump::Major, false), v("2.0.0"));
+        assert_eq!(v("1.2.3").bump(Bump::Minor, false), v("1.3.0"));
+        assert_eq!(v("1.2.3").bump(Bump::Patch, false), v("1.2.4"));
+        assert_eq!(v("0.2.3").bump(Bump::Major, true), v("0.3.0"));
+        assert_eq!(v("0.2.3").bump(Bump::Minor, true), v("0.2.4"));
+        assert_eq!(v("2.0.0-rc.1").bump(Bump::Minor, false), v("2.0.0"));
+        assert_eq!(v("1.3.1-rc.1").bump(Bump::Minor, false), v("1.4.0"));
+    }
+
+    #[test]
+    fn prerelease() {
+        let next = v("1.3.0");
+        assert_eq!(next.with_prerelease("rc", &v("1.2.0")), v("1.3.0-rc.0"));
+        assert_eq!(
+            next.with_prerelease("rc", &v("1.3.0-rc.0")),
+            v("1.3.0-rc.1")
+        );
+        assert_eq!(
+            next.with_prerelease("beta", &v("1.3.0-rc.4")),
+            v("1.3.0-beta.0")
+        );
+    }
+
+    #[test]
+    fn latest() {
+        let tags = ["v1.2.0", "nightly", "v1.10.0-rc.1", "v1.9.3"];
+        assert_eq!(
+            latest_release(tags.iter().copied()),
+            Some(("v1.10.0-rc.1", v("1.10.0-rc.1")))
+        );
+        assert_eq!(latest_release(vec!["nightly"]), None);
+    }
+}